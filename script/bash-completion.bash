@@ -1,6 +1,6 @@
 _aikot() {
 	local i cur prev opts cmds
-	cmds="add browse clip completion edit help init list pwgen show version"
+	cmds="add audit browse check-strength clip completion edit help init insert list otp pwgen show type version"
 	COMPREPLY=()
 	cur=${COMP_WORDS[COMP_CWORD]}
 	prev=${COMP_WORDS[COMP_CWORD-1]}
@@ -8,8 +8,10 @@ _aikot() {
 		COMPREPLY=( $(compgen -W "$cmds" -- ${cur}) )
 	fi
 	case $prev in
-	browse|clip|edit|show)	COMPREPLY=( $(compgen -W "$(${COMP_WORDS[0]} list)" -- ${cur}) )
+	browse|check-strength|clip|edit|show|type)	COMPREPLY=( $(compgen -W "$(${COMP_WORDS[0]} list)" -- ${cur}) )
 				;;
+	otp)	COMPREPLY=( $(compgen -W "import insert $(${COMP_WORDS[0]} list)" -- ${cur}) )
+		;;
 	completion) COMPREPLY=( $(compgen -W "bash" -- ${cur}) )
 		    ;;
 	esac
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::Error;
+use sha2::{Digest, Sha256};
+
+use crate::entry::{url_from_name, Entry};
+use crate::hibp::PwnedFile;
+use crate::strength::estimate;
+
+pub struct AuditOptions {
+    pub min_length: usize,
+    pub min_score: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Finding {
+    Breached { name: String, count: u64 },
+    NoUrl { name: String },
+    Reused { names: Vec<String> },
+    Short { name: String, length: usize },
+    Weak { name: String, score: usize },
+}
+
+impl Finding {
+    // Name of the checked entry, or the first one of reused.
+    fn name(&self) -> &str {
+        match self {
+            Finding::Breached { name, .. }
+            | Finding::NoUrl { name }
+            | Finding::Short { name, .. }
+            | Finding::Weak { name, .. } => name,
+            Finding::Reused { names } => &names[0],
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Finding::Breached { name, count } => {
+                write!(f, "breached password: {} (seen {} times)", name, count)
+            }
+            Finding::NoUrl { name } => write!(f, "url not found: {}", name),
+            Finding::Reused { names } => write!(f, "reused password: {}", names.join(", ")),
+            Finding::Short { name, length } => {
+                write!(f, "short password: {} ({} characters)", name, length)
+            }
+            Finding::Weak { name, score } => {
+                write!(f, "weak password: {} (score {}/4)", name, score)
+            }
+        }
+    }
+}
+
+// Check each entry and drop it, keeping only the hash of its password
+// to find reused ones, so that decrypted secrets do not outlive their checks.
+// Entries may come in any order, findings are sorted by name.
+pub fn audit<I>(
+    entries: I,
+    opts: &AuditOptions,
+    mut pwned: Option<&mut PwnedFile>,
+) -> Result<Vec<Finding>, Error>
+where
+    I: IntoIterator<Item = (String, Entry)>,
+{
+    let mut findings = Vec::new();
+    let mut groups = HashMap::<Vec<u8>, Vec<String>>::new();
+    for (name, entry) in entries {
+        let pass = entry.password();
+        // Names like "web/github.com" are urls for browse, and typed entries have no site.
+        if entry.urls().is_empty() && url_from_name(&name).is_none() && entry.entry_type().is_none()
+        {
+            findings.push(Finding::NoUrl { name: name.clone() });
+        }
+        if pass.is_empty() {
+            continue;
+        }
+        let length = pass.chars().count();
+        if length < opts.min_length {
+            findings.push(Finding::Short {
+                name: name.clone(),
+                length,
+            });
+        }
+        let score = estimate(pass).score();
+        if score < opts.min_score {
+            findings.push(Finding::Weak {
+                name: name.clone(),
+                score,
+            });
+        }
+        if let Some(pwned) = pwned.as_mut() {
+            if let Some(count) = pwned.count(pass)? {
+                findings.push(Finding::Breached {
+                    name: name.clone(),
+                    count,
+                });
+            }
+        }
+        groups
+            .entry(Sha256::digest(pass.as_bytes()).to_vec())
+            .or_default()
+            .push(name);
+    }
+    // Stable, so that findings of an entry stay in the order of checks.
+    findings.sort_by(|a, b| a.name().cmp(b.name()));
+    let mut reused = groups
+        .into_values()
+        .filter(|names| names.len() > 1)
+        .map(|mut names| {
+            names.sort();
+            names
+        })
+        .collect::<Vec<_>>();
+    reused.sort();
+    findings.extend(reused.into_iter().map(|names| Finding::Reused { names }));
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::File;
+    use std::io::Write;
+
+    use super::*;
+
+    fn entries(secrets: &[(&str, &str)]) -> Vec<(String, Entry)> {
+        secrets
+            .iter()
+            .map(|(name, contents)| (name.to_string(), Entry::parse(contents)))
+            .collect()
+    }
+
+    #[test]
+    fn audit_entries() {
+        let secrets = [
+            ("a", "X7#kP2!qL9@zR4$w\nurl: https://a.example.com\n"),
+            ("b", "password\nurl: https://b.example.com\n"),
+            ("c", "X7#kP2!qL9@zR4$w\n"),
+            ("d", "Xk7#pQ\nurl: https://d.example.com\n"),
+            ("e", "\ntype: wifi\nssid: home\n"),
+            ("f", "password\nurl: https://f.example.com\n"),
+            ("web/g.example.com", "\n"),
+        ];
+        let opts = AuditOptions {
+            min_length: 12,
+            min_score: 3,
+        };
+        // Entries come in the order they are decrypted.
+        let reversed = audit(entries(&secrets).into_iter().rev(), &opts, None).unwrap();
+        let findings = audit(entries(&secrets), &opts, None).unwrap();
+        assert_eq!(reversed, findings);
+        assert_eq!(
+            findings,
+            vec![
+                Finding::Short {
+                    name: "b".to_string(),
+                    length: 8
+                },
+                Finding::Weak {
+                    name: "b".to_string(),
+                    score: 0
+                },
+                Finding::NoUrl {
+                    name: "c".to_string()
+                },
+                Finding::Short {
+                    name: "d".to_string(),
+                    length: 6
+                },
+                Finding::Short {
+                    name: "f".to_string(),
+                    length: 8
+                },
+                Finding::Weak {
+                    name: "f".to_string(),
+                    score: 0
+                },
+                Finding::Reused {
+                    names: vec!["a".to_string(), "c".to_string()]
+                },
+                Finding::Reused {
+                    names: vec!["b".to_string(), "f".to_string()]
+                },
+            ]
+        );
+        assert_eq!(
+            format!("{}", findings[0]),
+            "short password: b (8 characters)"
+        );
+        assert_eq!(format!("{}", findings[6]), "reused password: a, c");
+    }
+
+    #[test]
+    fn audit_breached() {
+        let mut path = env::temp_dir();
+        path.push("aikot-audit-pwned.txt");
+        let mut f = File::create(&path).unwrap();
+        write!(f, "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824\r\n").unwrap();
+        drop(f);
+        let mut pwned = PwnedFile::open(&path).unwrap();
+        let entries = entries(&[("a", "password\nurl: https://a.example.com\n")]);
+        let opts = AuditOptions {
+            min_length: 0,
+            min_score: 0,
+        };
+        assert_eq!(
+            audit(entries, &opts, Some(&mut pwned)).unwrap(),
+            vec![Finding::Breached {
+                name: "a".to_string(),
+                count: 9545824
+            }]
+        );
+    }
+}
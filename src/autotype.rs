@@ -0,0 +1,238 @@
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::Error;
+
+use crate::err::AikotError;
+
+pub const DEFAULT_SEQUENCE: &str = "{user}{tab}{pass}{enter}";
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Token {
+    Field(String),
+    Key(Key),
+    Text(String),
+}
+
+// Token whose field is replaced by its value, ready to be typed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Resolved {
+    Key(Key),
+    Text(String),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Key {
+    Enter,
+    Space,
+    Tab,
+}
+
+impl Key {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "enter" => Some(Key::Enter),
+            "space" => Some(Key::Space),
+            "tab" => Some(Key::Tab),
+            _ => None,
+        }
+    }
+
+    fn keysym(&self) -> &'static str {
+        match self {
+            Key::Enter => "Return",
+            Key::Space => "space",
+            Key::Tab => "Tab",
+        }
+    }
+
+    fn keycode(&self) -> u16 {
+        match self {
+            Key::Enter => 28,
+            Key::Space => 57,
+            Key::Tab => 15,
+        }
+    }
+}
+
+pub fn parse_sequence(seq: &str) -> Result<Vec<Token>, AikotError> {
+    let mut tokens = vec![];
+    let mut rest = seq;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('{') {
+            let end = after.find('}').ok_or_else(|| AikotError::InvalidSequence {
+                seq: seq.to_string(),
+            })?;
+            let name = &after[..end];
+            if name.is_empty() {
+                return Err(AikotError::InvalidSequence {
+                    seq: seq.to_string(),
+                });
+            }
+            if let Some(key) = Key::from_name(name) {
+                tokens.push(Token::Key(key));
+            } else {
+                tokens.push(Token::Field(name.to_string()));
+            }
+            rest = &after[end + 1..];
+        } else {
+            let end = rest.find('{').unwrap_or(rest.len());
+            tokens.push(Token::Text(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+    }
+    Ok(tokens)
+}
+
+pub enum Typer {
+    Wtype,
+    Xdotool,
+    Ydotool,
+    // Custom typer is called as "CMD type" with text in stdin, and "CMD key KEYSYM".
+    Custom(String),
+}
+
+impl Typer {
+    pub fn new(name: Option<&str>) -> Self {
+        match name {
+            Some("wtype") => Typer::Wtype,
+            Some("xdotool") => Typer::Xdotool,
+            Some("ydotool") => Typer::Ydotool,
+            Some(cmd) => Typer::Custom(cmd.to_string()),
+            None => {
+                if env::var_os("WAYLAND_DISPLAY").is_some() {
+                    Typer::Wtype
+                } else {
+                    Typer::Xdotool
+                }
+            }
+        }
+    }
+
+    pub fn type_text(&self, text: &str) -> Result<(), Error> {
+        let mut cmd = match self {
+            Typer::Wtype => command("wtype", &["-"]),
+            Typer::Xdotool => command("xdotool", &["type", "--clearmodifiers", "--file", "-"]),
+            Typer::Ydotool => command("ydotool", &["type", "--file", "-"]),
+            Typer::Custom(typer) => custom_command(typer, &["type"]),
+        };
+        run(&mut cmd, Some(text))
+    }
+
+    pub fn press_key(&self, key: Key) -> Result<(), Error> {
+        let mut cmd = match self {
+            Typer::Wtype => command("wtype", &["-k", key.keysym()]),
+            Typer::Xdotool => command("xdotool", &["key", "--clearmodifiers", key.keysym()]),
+            Typer::Ydotool => {
+                let down = format!("{}:1", key.keycode());
+                let up = format!("{}:0", key.keycode());
+                command("ydotool", &["key", &down, &up])
+            }
+            Typer::Custom(typer) => custom_command(typer, &["key", key.keysym()]),
+        };
+        run(&mut cmd, None)
+    }
+}
+
+fn command(program: &str, args: &[&str]) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    cmd
+}
+
+fn custom_command(typer: &str, args: &[&str]) -> Command {
+    let mut words = typer.split_whitespace();
+    let mut cmd = Command::new(words.next().unwrap_or_default());
+    cmd.args(words).args(args);
+    cmd
+}
+
+fn run(cmd: &mut Command, input: Option<&str>) -> Result<(), Error> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AikotError::CommandFail {
+            stderr: format!("{}: {}", cmd.get_program().to_string_lossy(), e),
+        })?;
+    let mut child_stdin = child.stdin.take().unwrap();
+    if let Some(input) = input {
+        child_stdin.write_all(input.as_bytes())?;
+    }
+    drop(child_stdin);
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(AikotError::CommandFail {
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_default_sequence() {
+        assert_eq!(
+            parse_sequence(DEFAULT_SEQUENCE).unwrap(),
+            vec![
+                Token::Field("user".to_string()),
+                Token::Key(Key::Tab),
+                Token::Field("pass".to_string()),
+                Token::Key(Key::Enter),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_sequence_with_text() {
+        assert_eq!(
+            parse_sequence("id={user}{space}!").unwrap(),
+            vec![
+                Token::Text("id=".to_string()),
+                Token::Field("user".to_string()),
+                Token::Key(Key::Space),
+                Token::Text("!".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_sequence() {
+        assert!(parse_sequence("{user}{tab").is_err());
+        assert!(parse_sequence("{}").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn custom_typer_called() {
+        use std::fs::{self, File};
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut dir = env::temp_dir();
+        dir.push(format!("aikot-typer-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("typer");
+        let log = dir.join("log");
+        let mut f = File::create(&script).unwrap();
+        writeln!(
+            f,
+            "#!/bin/sh\nif [ \"$1\" = type ]; then echo \"type $(cat)\"; else echo \"$@\"; fi >> {}",
+            log.display()
+        )
+        .unwrap();
+        drop(f);
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let typer = Typer::new(Some(script.to_str().unwrap()));
+        typer.type_text("foo bar").unwrap();
+        typer.press_key(Key::Tab).unwrap();
+        assert_eq!(fs::read_to_string(&log).unwrap(), "type foo bar\nkey Tab\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,17 @@
+use std::env;
+use std::io::ErrorKind;
 use std::process::Command;
 
+use anyhow::Error;
+
+use crate::config::Config;
+use crate::err::AikotError;
+
+#[cfg(unix)]
+const BROWSER_SEPARATOR: char = ':';
+#[cfg(windows)]
+const BROWSER_SEPARATOR: char = ';';
+
 #[cfg(all(unix, not(target_os = "macos")))]
 pub fn browser_command() -> Command {
     Command::new("xdg-open")
@@ -17,4 +29,104 @@ pub fn browser_command() -> Command {
     cmd
 }
 
+// Commands to be tried in order, from $BROWSER, "browser" config or the platform default.
+pub fn browser_commands(config: &Config, url: &str) -> Vec<Command> {
+    let spec = env::var("BROWSER")
+        .ok()
+        .or_else(|| config.get("browser").map(|s| s.to_string()));
+    if let Some(spec) = spec {
+        spec.split(BROWSER_SEPARATOR)
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| command_from_spec(s, url))
+            .collect()
+    } else {
+        let mut cmd = browser_command();
+        cmd.arg(url);
+        vec![cmd]
+    }
+}
+
+pub fn open_url(config: &Config, url: &str, wait: bool) -> Result<(), Error> {
+    for mut cmd in browser_commands(config, url) {
+        match cmd.spawn() {
+            Ok(mut child) => {
+                if wait {
+                    child.wait()?;
+                }
+                return Ok(());
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(AikotError::BrowserNotFound.into())
+}
+
+fn command_from_spec(spec: &str, url: &str) -> Command {
+    let mut words = spec.split_whitespace();
+    let mut cmd = Command::new(words.next().unwrap_or_default());
+    if spec.contains("%s") {
+        cmd.args(words.map(|w| w.replace("%s", url)));
+    } else {
+        cmd.args(words).arg(url);
+    }
+    cmd
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::OsStr;
+
+    use super::*;
 
+    fn program_and_args(cmd: &Command) -> (&OsStr, Vec<&OsStr>) {
+        (cmd.get_program(), cmd.get_args().collect())
+    }
+
+    #[test]
+    fn command_from_spec_append_url() {
+        let cmd = command_from_spec("firefox -P work", "https://example.com/");
+        assert_eq!(
+            program_and_args(&cmd),
+            (
+                OsStr::new("firefox"),
+                vec![
+                    OsStr::new("-P"),
+                    OsStr::new("work"),
+                    OsStr::new("https://example.com/")
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn command_from_spec_substitute_url() {
+        let cmd = command_from_spec("w3m --url=%s -o", "https://example.com/");
+        assert_eq!(
+            program_and_args(&cmd),
+            (
+                OsStr::new("w3m"),
+                vec![OsStr::new("--url=https://example.com/"), OsStr::new("-o")]
+            )
+        );
+    }
+
+    #[test]
+    fn browser_commands_from_config() {
+        env::remove_var("BROWSER");
+        let config = Config::parse("browser = lynx:chromium --incognito\n");
+        let cmds = browser_commands(&config, "https://example.com/");
+        assert_eq!(cmds.len(), 2);
+        assert_eq!(cmds[0].get_program(), "lynx");
+        assert_eq!(
+            program_and_args(&cmds[1]),
+            (
+                OsStr::new("chromium"),
+                vec![
+                    OsStr::new("--incognito"),
+                    OsStr::new("https://example.com/")
+                ]
+            )
+        );
+    }
+}
@@ -1,8 +1,53 @@
+use std::thread::sleep;
+use std::time::Duration;
+
 use anyhow::{anyhow, Error};
 use clipboard_macos::Clipboard;
 
-pub fn set_clip(text: &str) -> Result<(), Error> {
+use super::unclip::{read_unclip, spawn_unclip};
+use super::{enter_presses, wait_enter, ClipBackend, ClipOptions};
+use crate::err::AikotError;
+
+pub fn set_clip_sequence(
+    texts: &[&str],
+    opts: &ClipOptions,
+    copied: &mut dyn FnMut(usize),
+) -> Result<(), Error> {
+    if opts.backend != ClipBackend::Auto {
+        return Err(AikotError::UnsupportedBackend {
+            backend: opts.backend.to_string(),
+        }
+        .into());
+    }
+    let Some((text, rest)) = texts.split_last() else {
+        return Ok(());
+    };
+    let mut cb = Clipboard::new().map_err(|e| anyhow!("{}", e.to_string()))?;
+    let prev = cb.read().ok();
+    let mut presses = None;
+    for (i, text) in rest.iter().enumerate() {
+        cb.write(text.to_string())
+            .map_err(|e| anyhow!("{}", e.to_string()))?;
+        copied(i);
+        wait_enter(presses.get_or_insert_with(enter_presses), opts.timeout);
+    }
+    if opts.timeout > 0 {
+        let _ = spawn_unclip(opts.timeout, text, prev.as_deref());
+    }
+    cb.write(text.to_string())
+        .map_err(|e| anyhow!("{}", e.to_string()))?;
+    copied(rest.len());
+    Ok(())
+}
+
+pub fn clear_clip(timeout: u64) -> Result<(), Error> {
+    let (text, prev) = read_unclip()?;
+    sleep(Duration::from_secs(timeout));
     let mut cb = Clipboard::new().map_err(|e| anyhow!("{}", e.to_string()))?;
-    cb.write(text.to_string()).map_err(|e| anyhow!("{}", e.to_string()))?;
+    if cb.read().ok().as_deref() != Some(text.as_str()) {
+        return Ok(());
+    }
+    cb.write(prev.unwrap_or_default())
+        .map_err(|e| anyhow!("{}", e.to_string()))?;
     Ok(())
 }
@@ -1,7 +1,12 @@
+#[cfg(any(windows, target_os = "macos", test))]
+mod unclip;
+
 #[cfg(all(unix, not(target_os = "macos")))]
-mod x11;
+mod osc52;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod wayland;
 #[cfg(all(unix, not(target_os = "macos")))]
-pub use x11::*;
+mod x11;
 
 #[cfg(target_os = "macos")]
 mod macos;
@@ -12,3 +17,233 @@ pub use macos::*;
 mod windows;
 #[cfg(windows)]
 pub use self::windows::*;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::env;
+use std::fmt;
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::os::unix::io::RawFd;
+use std::str::FromStr;
+#[cfg(any(windows, target_os = "macos"))]
+use std::sync::mpsc::{self, Receiver};
+#[cfg(any(windows, target_os = "macos"))]
+use std::thread;
+#[cfg(any(windows, target_os = "macos"))]
+use std::time::Duration;
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::time::{Duration, Instant};
+
+use anyhow::Error;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+use crate::io::unix::poll_readable;
+
+// Clipboard history managers skip contents offered with this target.
+#[cfg(all(unix, not(target_os = "macos")))]
+const KDE_PASSWORD_MANAGER_HINT: &str = "x-kde-passwordManagerHint";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ClipBackend {
+    Auto,
+    Osc52,
+    Wayland,
+    X11,
+}
+
+impl fmt::Display for ClipBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClipBackend::Auto => write!(f, "auto"),
+            ClipBackend::Osc52 => write!(f, "osc52"),
+            ClipBackend::Wayland => write!(f, "wayland"),
+            ClipBackend::X11 => write!(f, "x11"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ClipSelection {
+    Both,
+    Clipboard,
+    Primary,
+}
+
+impl FromStr for ClipSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "both" => Ok(ClipSelection::Both),
+            "clipboard" => Ok(ClipSelection::Clipboard),
+            "primary" => Ok(ClipSelection::Primary),
+            _ => Err(format!("Unknown selection: {}", s)),
+        }
+    }
+}
+
+pub struct ClipOptions {
+    pub backend: ClipBackend,
+    pub selection: ClipSelection,
+    pub timeout: u64,
+}
+
+pub fn set_clip(text: &str, opts: &ClipOptions) -> Result<(), Error> {
+    set_clip_sequence(&[text], opts, &mut |_| {})
+}
+
+// Copies texts one by one, calling copied(i) when the i-th text is on the clipboard,
+// moves on when it is pasted, Enter is pressed or the timeout passes,
+// and clears the clipboard after the last one as usual.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn set_clip_sequence(
+    texts: &[&str],
+    opts: &ClipOptions,
+    copied: &mut dyn FnMut(usize),
+) -> Result<(), Error> {
+    match resolve_backend(opts.backend) {
+        ClipBackend::Osc52 => osc52::set_clip_sequence(texts, opts, copied),
+        ClipBackend::Wayland => wayland::set_clip_sequence(texts, opts, copied),
+        _ => x11::set_clip_sequence(texts, opts, copied),
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn resolve_backend(backend: ClipBackend) -> ClipBackend {
+    if backend != ClipBackend::Auto {
+        backend
+    } else if env::var_os("WAYLAND_DISPLAY").is_some() {
+        ClipBackend::Wayland
+    } else if env::var_os("DISPLAY").is_some() {
+        ClipBackend::X11
+    } else {
+        ClipBackend::Osc52
+    }
+}
+
+// Text offered on a descriptor that becomes readable when something happens to it.
+#[cfg(all(unix, not(target_os = "macos")))]
+trait PasteServer {
+    fn fds(&self) -> Vec<RawFd>;
+
+    // Handle what happened, true if the text was pasted or replaced.
+    fn pasted(&mut self) -> Result<bool, Error>;
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[derive(Debug, PartialEq)]
+enum Wake {
+    Enter,
+    Pasted,
+    Timeout,
+}
+
+// Wait for a line on the input, a paste of the served text or the deadline,
+// without threads since the process forks to clear the clipboard afterwards.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn wait_user(
+    input: RawFd,
+    mut server: Option<&mut dyn PasteServer>,
+    deadline: Option<Instant>,
+) -> Result<Wake, Error> {
+    loop {
+        let mut fds = vec![input];
+        if let Some(server) = server.as_ref() {
+            fds.extend(server.fds());
+        }
+        match poll_readable(&fds, deadline)? {
+            None => return Ok(Wake::Timeout),
+            Some(0) => {
+                let mut buf = [0u8; 256];
+                unsafe { libc::read(input, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+                return Ok(Wake::Enter);
+            }
+            Some(_) => {
+                if let Some(server) = server.as_mut() {
+                    if server.pasted()? {
+                        return Ok(Wake::Pasted);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn deadline(timeout: u64) -> Option<Instant> {
+    (timeout > 0).then(|| Instant::now() + Duration::from_secs(timeout))
+}
+
+// Read key presses in one thread so that a timed out wait does not take the next one.
+#[cfg(any(windows, target_os = "macos"))]
+fn enter_presses() -> Receiver<()> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for _line in std::io::stdin().lines() {
+            if tx.send(()).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(any(windows, target_os = "macos"))]
+fn wait_enter(presses: &Receiver<()>, timeout: u64) {
+    if timeout == 0 {
+        let _ = presses.recv();
+    } else {
+        let _ = presses.recv_timeout(Duration::from_secs(timeout));
+    }
+}
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod test {
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+
+    use super::*;
+
+    // Reads notifications from a socket, "p" for a paste.
+    struct FakeServer(UnixStream);
+
+    impl PasteServer for FakeServer {
+        fn fds(&self) -> Vec<RawFd> {
+            vec![self.0.as_raw_fd()]
+        }
+
+        fn pasted(&mut self) -> Result<bool, Error> {
+            let mut buf = [0u8; 1];
+            self.0.read_exact(&mut buf)?;
+            Ok(buf[0] == b'p')
+        }
+    }
+
+    #[test]
+    fn wait_user_sequence() {
+        let (mut input, input_fd) = UnixStream::pair().unwrap();
+        let (mut events, server) = UnixStream::pair().unwrap();
+        let mut server = FakeServer(server);
+        let soon = || Some(Instant::now() + Duration::from_millis(100));
+
+        events.write_all(b"xp").unwrap();
+        assert_eq!(
+            wait_user(input_fd.as_raw_fd(), Some(&mut server), soon()).unwrap(),
+            Wake::Pasted
+        );
+        input.write_all(b"\n").unwrap();
+        assert_eq!(
+            wait_user(input_fd.as_raw_fd(), Some(&mut server), soon()).unwrap(),
+            Wake::Enter
+        );
+        events.write_all(b"x").unwrap();
+        assert_eq!(
+            wait_user(input_fd.as_raw_fd(), Some(&mut server), soon()).unwrap(),
+            Wake::Timeout
+        );
+        assert_eq!(
+            wait_user(input_fd.as_raw_fd(), None, soon()).unwrap(),
+            Wake::Timeout
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::Error;
+use data_encoding::BASE64;
+
+use super::{deadline, wait_user, ClipOptions, ClipSelection};
+use crate::io::unix::daemonize;
+
+pub fn set_clip_sequence(
+    texts: &[&str],
+    opts: &ClipOptions,
+    copied: &mut dyn FnMut(usize),
+) -> Result<(), Error> {
+    let mut tty = OpenOptions::new().write(true).open("/dev/tty")?;
+    for (i, text) in texts.iter().enumerate() {
+        if i > 0 {
+            // Terminals do not tell when the clipboard is pasted.
+            wait_user(libc::STDIN_FILENO, None, deadline(opts.timeout))?;
+        }
+        tty.write_all(osc52_sequence(text, opts.selection).as_bytes())?;
+        copied(i);
+    }
+    if opts.timeout == 0 {
+        return Ok(());
+    }
+
+    unsafe {
+        daemonize()?;
+    };
+
+    sleep(Duration::from_secs(opts.timeout));
+    // Terminal clipboard cannot be read back in general, so clear it unconditionally.
+    tty.write_all(osc52_sequence("", opts.selection).as_bytes())?;
+    Ok(())
+}
+
+fn osc52_sequence(text: &str, selection: ClipSelection) -> String {
+    let tmux = env::var_os("TMUX").is_some();
+    let screen = env::var("TERM").is_ok_and(|term| term.starts_with("screen"));
+    wrap_passthrough(
+        &format!(
+            "\x1b]52;{};{}\x07",
+            selection_param(selection),
+            BASE64.encode(text.as_bytes())
+        ),
+        tmux,
+        screen,
+    )
+}
+
+fn selection_param(selection: ClipSelection) -> &'static str {
+    match selection {
+        ClipSelection::Both => "pc",
+        ClipSelection::Clipboard => "c",
+        ClipSelection::Primary => "p",
+    }
+}
+
+fn wrap_passthrough(seq: &str, tmux: bool, screen: bool) -> String {
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", seq.replace('\x1b', "\x1b\x1b"))
+    } else if screen {
+        format!("\x1bP{}\x1b\\", seq)
+    } else {
+        seq.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wrap_passthrough_none() {
+        assert_eq!(
+            wrap_passthrough("\x1b]52;c;Zm9v\x07", false, false),
+            "\x1b]52;c;Zm9v\x07"
+        );
+    }
+
+    #[test]
+    fn wrap_passthrough_tmux() {
+        assert_eq!(
+            wrap_passthrough("\x1b]52;c;Zm9v\x07", true, false),
+            "\x1bPtmux;\x1b\x1b]52;c;Zm9v\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn wrap_passthrough_screen() {
+        assert_eq!(
+            wrap_passthrough("\x1b]52;c;Zm9v\x07", false, true),
+            "\x1bP\x1b]52;c;Zm9v\x07\x1b\\"
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use std::io::{Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::process::{Command, Stdio};
+use std::ptr;
+
+use anyhow::{anyhow, Error};
+use wl_clipboard_rs::copy::{self, MimeSource, Options, ServeRequests, Source};
+use wl_clipboard_rs::paste::{self, ClipboardType, MimeType, Seat};
+
+use super::{
+    deadline, wait_user, ClipOptions, ClipSelection, PasteServer, KDE_PASSWORD_MANAGER_HINT,
+};
+use crate::env::find_executable;
+use crate::err::AikotError;
+use crate::io::unix::{daemonize, poll_readable, UnixError};
+
+// wl-clipboard-rs needs the data-control protocol, which compositors like GNOME do not have,
+// so wl-copy and wl-paste serve the clipboard there.
+#[derive(Copy, Clone, PartialEq)]
+enum Tool {
+    DataControl,
+    WlCopy,
+}
+
+pub fn set_clip_sequence(
+    texts: &[&str],
+    opts: &ClipOptions,
+    copied: &mut dyn FnMut(usize),
+) -> Result<(), Error> {
+    let Some((text, rest)) = texts.split_last() else {
+        return Ok(());
+    };
+    let primaries = primary_flags(opts.selection);
+    let tool = detect_tool(&primaries)?;
+    let mut prevs = vec![];
+    for primary in &primaries {
+        prevs.push(get_text(tool, *primary)?);
+    }
+    for (i, text) in rest.iter().enumerate() {
+        let mut server = SecretServer::spawn(tool, text, &primaries)?;
+        copied(i);
+        wait_user(
+            libc::STDIN_FILENO,
+            Some(&mut server),
+            deadline(opts.timeout),
+        )?;
+    }
+    copied(rest.len());
+
+    unsafe {
+        daemonize()?;
+    };
+
+    let server = SecretServer::spawn(tool, text, &primaries)?;
+    poll_readable(&server.fds(), deadline(opts.timeout))?;
+    drop(server);
+
+    for (primary, prev) in primaries.iter().zip(prevs) {
+        // Clipboard managers may have taken over our secret after it is pasted.
+        if let Some(current) = get_text(tool, *primary)? {
+            if current != *text {
+                continue;
+            }
+        }
+        match (tool, prev) {
+            (Tool::DataControl, Some(prev)) => {
+                // Keep serving the previous contents in the background until someone else takes the ownership.
+                let mut copy_opts = Options::new();
+                copy_opts.clipboard(copy_clipboard_type(*primary));
+                copy_opts.copy(
+                    Source::Bytes(prev.into_bytes().into()),
+                    copy::MimeType::Text,
+                )?;
+            }
+            (Tool::DataControl, None) => {
+                copy::clear(copy_clipboard_type(*primary), copy::Seat::All)?;
+            }
+            (Tool::WlCopy, Some(prev)) => {
+                let mut child = wl_copy(*primary).stdin(Stdio::piped()).spawn()?;
+                child.stdin.take().unwrap().write_all(prev.as_bytes())?;
+                child.wait()?;
+            }
+            (Tool::WlCopy, None) => {
+                wl_copy(*primary).arg("--clear").status()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn detect_tool(primaries: &[bool]) -> Result<Tool, Error> {
+    let clipboard = paste_clipboard_type(primaries[0]);
+    match paste::get_contents(clipboard, Seat::Unspecified, MimeType::Text) {
+        Err(paste::Error::MissingProtocol { .. })
+        | Err(paste::Error::PrimarySelectionUnsupported) => {
+            for name in ["wl-copy", "wl-paste"] {
+                if find_executable(name).is_none() {
+                    return Err(AikotError::CommandNotFound {
+                        name: name.to_string(),
+                    }
+                    .into());
+                }
+            }
+            Ok(Tool::WlCopy)
+        }
+        _ => Ok(Tool::DataControl),
+    }
+}
+
+// Processes serving the secret until it is pasted once, whose exits close the pipes
+// so that the parent can poll for them without threads.
+struct SecretServer {
+    pids: Vec<libc::pid_t>,
+    pipes: Vec<OwnedFd>,
+}
+
+impl SecretServer {
+    fn spawn(tool: Tool, text: &str, primaries: &[bool]) -> Result<Self, Error> {
+        let mut server = SecretServer {
+            pids: vec![],
+            pipes: vec![],
+        };
+        match tool {
+            Tool::DataControl => server.fork_data_control(text, primaries)?,
+            Tool::WlCopy => {
+                for primary in primaries {
+                    let mut child = wl_copy(*primary)
+                        .arg("--paste-once")
+                        .arg("--foreground")
+                        .stdin(Stdio::piped())
+                        .stdout(Stdio::piped())
+                        .spawn()?;
+                    server.pids.push(child.id() as libc::pid_t);
+                    server.pipes.push(child.stdout.take().unwrap().into());
+                    child.stdin.take().unwrap().write_all(text.as_bytes())?;
+                }
+            }
+        }
+        Ok(server)
+    }
+
+    fn fork_data_control(&mut self, text: &str, primaries: &[bool]) -> Result<(), Error> {
+        let sources = vec![
+            MimeSource {
+                source: Source::Bytes(text.as_bytes().into()),
+                mime_type: copy::MimeType::Text,
+            },
+            // Reading this hint does not count as the paste.
+            MimeSource {
+                source: Source::Bytes(b"secret".to_vec().into()),
+                mime_type: copy::MimeType::Specific(KDE_PASSWORD_MANAGER_HINT.to_string()),
+            },
+        ];
+        let clipboard = if primaries.len() > 1 {
+            copy::ClipboardType::Both
+        } else {
+            copy_clipboard_type(primaries[0])
+        };
+        let mut copy_opts = Options::new();
+        copy_opts
+            .clipboard(clipboard)
+            .serve_requests(ServeRequests::Only(1))
+            .foreground(true);
+
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+            return Err(UnixError::new("pipe".to_string()).into());
+        }
+        let pid = unsafe { libc::fork() };
+        if pid == -1 {
+            return Err(anyhow!("fork() failed"));
+        } else if pid == 0 {
+            unsafe { libc::close(fds[0]) };
+            let result = copy_opts.copy_multi(sources);
+            unsafe { libc::_exit(if result.is_ok() { 0 } else { 1 }) };
+        }
+        unsafe { libc::close(fds[1]) };
+        self.pids.push(pid);
+        self.pipes.push(unsafe { OwnedFd::from_raw_fd(fds[0]) });
+        Ok(())
+    }
+}
+
+impl PasteServer for SecretServer {
+    fn fds(&self) -> Vec<RawFd> {
+        self.pipes.iter().map(|pipe| pipe.as_raw_fd()).collect()
+    }
+
+    fn pasted(&mut self) -> Result<bool, Error> {
+        Ok(true)
+    }
+}
+
+impl Drop for SecretServer {
+    fn drop(&mut self) {
+        for pid in &self.pids {
+            unsafe {
+                libc::kill(*pid, libc::SIGTERM);
+                libc::waitpid(*pid, ptr::null_mut(), 0);
+            }
+        }
+    }
+}
+
+fn get_text(tool: Tool, primary: bool) -> Result<Option<String>, Error> {
+    if tool == Tool::WlCopy {
+        return Ok(wl_paste(primary));
+    }
+    let clipboard = paste_clipboard_type(primary);
+    match paste::get_contents(clipboard, Seat::Unspecified, MimeType::Text) {
+        Ok((mut pipe, _)) => {
+            let mut buf = String::new();
+            Ok(pipe.read_to_string(&mut buf).ok().map(|_| buf))
+        }
+        Err(paste::Error::NoSeats)
+        | Err(paste::Error::ClipboardEmpty)
+        | Err(paste::Error::NoMimeType) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn primary_flags(selection: ClipSelection) -> Vec<bool> {
+    match selection {
+        ClipSelection::Both => vec![true, false],
+        ClipSelection::Clipboard => vec![false],
+        ClipSelection::Primary => vec![true],
+    }
+}
+
+fn paste_clipboard_type(primary: bool) -> ClipboardType {
+    if primary {
+        ClipboardType::Primary
+    } else {
+        ClipboardType::Regular
+    }
+}
+
+fn copy_clipboard_type(primary: bool) -> copy::ClipboardType {
+    if primary {
+        copy::ClipboardType::Primary
+    } else {
+        copy::ClipboardType::Regular
+    }
+}
+
+fn wl_copy(primary: bool) -> Command {
+    let mut cmd = Command::new("wl-copy");
+    if primary {
+        cmd.arg("--primary");
+    }
+    cmd
+}
+
+fn wl_paste(primary: bool) -> Option<String> {
+    let mut cmd = Command::new("wl-paste");
+    if primary {
+        cmd.arg("--primary");
+    }
+    let output = cmd
+        .arg("--no-newline")
+        .arg("--type")
+        .arg("text")
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
@@ -1,5 +1,3 @@
-use std::env::current_exe;
-use std::process::Command;
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -8,11 +6,42 @@ use anyhow::{anyhow, Error};
 use windows::core::{Result, HSTRING};
 use windows::ApplicationModel::DataTransfer::{Clipboard, ClipboardContentOptions, DataPackage};
 
-pub fn set_clip(text: &str) -> std::result::Result<(), Error> {
-    if let Ok(exe) = current_exe() {
-        let _ = Command::new(exe).arg("unclip").spawn();
+use super::unclip::{read_unclip, spawn_unclip};
+use super::{enter_presses, wait_enter, ClipBackend, ClipOptions};
+use crate::err::AikotError;
+
+pub fn set_clip_sequence(
+    texts: &[&str],
+    opts: &ClipOptions,
+    copied: &mut dyn FnMut(usize),
+) -> std::result::Result<(), Error> {
+    if opts.backend != ClipBackend::Auto {
+        return Err(AikotError::UnsupportedBackend {
+            backend: opts.backend.to_string(),
+        }
+        .into());
+    }
+    let Some((text, rest)) = texts.split_last() else {
+        return Ok(());
+    };
+    let prev = get_clip_win().ok();
+    let mut presses = None;
+    for (i, text) in rest.iter().enumerate() {
+        set_clip_win(text).map_err(|e| anyhow!("{}", e.message()))?;
+        copied(i);
+        wait_enter(presses.get_or_insert_with(enter_presses), opts.timeout);
     }
-    set_clip_win(text).map_err(|e| anyhow!("{}", e.message()))
+    if opts.timeout > 0 {
+        let _ = spawn_unclip(opts.timeout, text, prev.as_deref());
+    }
+    set_clip_win(text).map_err(|e| anyhow!("{}", e.message()))?;
+    copied(rest.len());
+    Ok(())
+}
+
+fn get_clip_win() -> Result<String> {
+    let view = Clipboard::GetContent()?;
+    Ok(view.GetTextAsync()?.get()?.to_string_lossy())
 }
 
 fn set_clip_win(text: &str) -> Result<()> {
@@ -26,7 +55,15 @@ fn set_clip_win(text: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn clear_clip() -> std::result::Result<(), Error> {
-    sleep(Duration::from_secs(45));
-    Clipboard::Clear().map_err(|e| anyhow!("{}", e.message()))
+pub fn clear_clip(timeout: u64) -> std::result::Result<(), Error> {
+    let (text, prev) = read_unclip()?;
+    sleep(Duration::from_secs(timeout));
+    if get_clip_win().ok().as_deref() != Some(text.as_str()) {
+        return Ok(());
+    }
+    if let Some(prev) = prev {
+        set_clip_win(&prev).map_err(|e| anyhow!("{}", e.message()))
+    } else {
+        Clipboard::Clear().map_err(|e| anyhow!("{}", e.message()))
+    }
 }
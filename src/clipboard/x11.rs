@@ -1,40 +1,296 @@
-use std::ffi::CString;
-use std::process::exit;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::thread::sleep;
 use std::time::Duration;
 
-use anyhow::{anyhow, Error};
-use arboard::Clipboard;
+use anyhow::Error;
+use arboard::{ClearExtLinux, Clipboard, GetExtLinux, LinuxClipboardKind, SetExtLinux};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ConnectionExt, CreateWindowAux, EventMask, PropMode, SelectionNotifyEvent,
+    SelectionRequestEvent, Window, WindowClass, SELECTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::{COPY_DEPTH_FROM_PARENT, CURRENT_TIME};
+
+use super::{deadline, wait_user, ClipOptions, ClipSelection, PasteServer};
+use crate::io::unix::daemonize;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        CLIPBOARD,
+        TARGETS,
+        TEXT,
+        UTF8_STRING,
+        PASSWORD_MANAGER_HINT: b"x-kde-passwordManagerHint",
+    }
+}
+
+pub fn set_clip_sequence(
+    texts: &[&str],
+    opts: &ClipOptions,
+    copied: &mut dyn FnMut(usize),
+) -> Result<(), Error> {
+    let Some((text, rest)) = texts.split_last() else {
+        return Ok(());
+    };
+    let kinds = clipboard_kinds(opts.selection);
+    // Dropping the clipboard joins its thread before the process forks.
+    let prevs = {
+        let mut cb = Clipboard::new()?;
+        kinds
+            .iter()
+            .map(|kind| cb.get().clipboard(*kind).text().ok())
+            .collect::<Vec<_>>()
+    };
+    for (i, text) in rest.iter().enumerate() {
+        let mut server = SelectionServer::new(text, opts.selection)?;
+        copied(i);
+        wait_user(
+            libc::STDIN_FILENO,
+            Some(&mut server),
+            deadline(opts.timeout),
+        )?;
+    }
+    copied(rest.len());
 
-pub fn set_clip(text: &str) -> Result<(), Error> {
     unsafe {
         daemonize()?;
     };
 
     let mut cb = Clipboard::new()?;
-    cb.set_text(text)?;
+    if opts.timeout == 0 {
+        let texts = kinds.iter().map(|kind| (*kind, *text)).collect::<Vec<_>>();
+        return serve_until_replaced(&mut cb, &texts, true);
+    }
+    for kind in &kinds {
+        cb.set()
+            .clipboard(*kind)
+            .exclude_from_history()
+            .text(*text)?;
+    }
+    sleep(Duration::from_secs(opts.timeout));
+
+    let mut restores = vec![];
+    for (kind, prev) in kinds.iter().zip(prevs.iter()) {
+        if cb.get().clipboard(*kind).text().ok().as_deref() != Some(*text) {
+            continue;
+        }
+        if let Some(prev) = prev {
+            restores.push((*kind, prev.as_str()));
+        } else {
+            cb.clear_with().clipboard(*kind)?;
+        }
+    }
+    serve_until_replaced(&mut cb, &restores, false)
+}
 
-    sleep(Duration::from_secs(45));
+// Keep serving the contents until someone else takes the ownership of the last selection.
+fn serve_until_replaced(
+    cb: &mut Clipboard,
+    texts: &[(LinuxClipboardKind, &str)],
+    secret: bool,
+) -> Result<(), Error> {
+    if let Some(((last_kind, last_text), rest)) = texts.split_last() {
+        for (kind, text) in rest {
+            let set = cb.set().clipboard(*kind);
+            if secret {
+                set.exclude_from_history().text(*text)?;
+            } else {
+                set.text(*text)?;
+            }
+        }
+        let set = cb.set().clipboard(*last_kind).wait();
+        if secret {
+            set.exclude_from_history().text(*last_text)?;
+        } else {
+            set.text(*last_text)?;
+        }
+    }
     Ok(())
 }
 
-unsafe fn daemonize() -> Result<(), Error> {
-    let child = libc::fork();
-    if child == -1 {
-        return Err(anyhow!("fork() failed"));
-    } else if child > 0 {
-        libc::_exit(0);
+// Owner of the selections serving one text until it is pasted or replaced,
+// so that the next text of a sequence can be copied right after the paste.
+struct SelectionServer {
+    conn: RustConnection,
+    window: Window,
+    atoms: Atoms,
+    text: Vec<u8>,
+}
+
+impl SelectionServer {
+    fn new(text: &str, selection: ClipSelection) -> Result<Self, Error> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let screen = &conn.setup().roots[screen_num];
+        let window = conn.generate_id()?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            screen.root_visual,
+            &CreateWindowAux::new(),
+        )?;
+        let atoms = Atoms::new(&conn)?.reply()?;
+        for kind in clipboard_kinds(selection) {
+            let selection = match kind {
+                LinuxClipboardKind::Primary => AtomEnum::PRIMARY.into(),
+                _ => atoms.CLIPBOARD,
+            };
+            conn.set_selection_owner(window, selection, CURRENT_TIME)?;
+        }
+        conn.flush()?;
+        Ok(SelectionServer {
+            conn,
+            window,
+            atoms,
+            text: text.as_bytes().to_vec(),
+        })
+    }
+
+    // Answer the request, true if the text itself was sent.
+    fn reply(&self, req: &SelectionRequestEvent) -> Result<bool, Error> {
+        let property = if req.property == u32::from(AtomEnum::NONE) {
+            req.target
+        } else {
+            req.property
+        };
+        let reply = selection_reply(&self.atoms, req.target, &self.text);
+        if let Some(reply) = &reply {
+            self.conn.change_property(
+                PropMode::REPLACE,
+                req.requestor,
+                property,
+                reply.kind,
+                reply.format,
+                reply.data.len() as u32 * 8 / u32::from(reply.format),
+                &reply.data,
+            )?;
+        }
+        let event = SelectionNotifyEvent {
+            response_type: SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time: req.time,
+            requestor: req.requestor,
+            selection: req.selection,
+            target: req.target,
+            property: if reply.is_some() {
+                property
+            } else {
+                AtomEnum::NONE.into()
+            },
+        };
+        self.conn
+            .send_event(false, req.requestor, EventMask::NO_EVENT, event)?;
+        Ok(reply.is_some_and(|reply| reply.pasted))
+    }
+}
+
+impl PasteServer for SelectionServer {
+    fn fds(&self) -> Vec<RawFd> {
+        vec![self.conn.stream().as_raw_fd()]
     }
-    let result = libc::setsid();
-    if result == -1 {
-        exit(0);
+
+    fn pasted(&mut self) -> Result<bool, Error> {
+        let mut pasted = false;
+        while let Some(event) = self.conn.poll_for_event()? {
+            match event {
+                Event::SelectionRequest(req) if req.owner == self.window => {
+                    pasted |= self.reply(&req)?;
+                }
+                Event::SelectionClear(_) => pasted = true,
+                _ => {}
+            }
+        }
+        self.conn.flush()?;
+        Ok(pasted)
     }
-    let dev_null = CString::new("/dev/null")?;
-    let fd = libc::open(dev_null.into_raw(), libc::O_RDWR, 0);
-    if fd != -1 {
-        libc::dup2(fd, libc::STDIN_FILENO);
-        libc::dup2(fd, libc::STDOUT_FILENO);
-        libc::dup2(fd, libc::STDERR_FILENO);
+}
+
+// Property to answer a selection request with, None to refuse it.
+struct SelectionReply {
+    kind: Atom,
+    format: u8,
+    data: Vec<u8>,
+    pasted: bool,
+}
+
+fn selection_reply(atoms: &Atoms, target: Atom, text: &[u8]) -> Option<SelectionReply> {
+    let string = Atom::from(AtomEnum::STRING);
+    if target == atoms.TARGETS {
+        let targets = [
+            atoms.TARGETS,
+            atoms.UTF8_STRING,
+            string,
+            atoms.TEXT,
+            atoms.PASSWORD_MANAGER_HINT,
+        ];
+        Some(SelectionReply {
+            kind: AtomEnum::ATOM.into(),
+            format: 32,
+            data: targets.iter().flat_map(|atom| atom.to_ne_bytes()).collect(),
+            pasted: false,
+        })
+    } else if target == atoms.PASSWORD_MANAGER_HINT {
+        // Clipboard history managers read the hint without pasting.
+        Some(SelectionReply {
+            kind: string,
+            format: 8,
+            data: b"secret".to_vec(),
+            pasted: false,
+        })
+    } else if [atoms.UTF8_STRING, string, atoms.TEXT].contains(&target) {
+        Some(SelectionReply {
+            kind: if target == string {
+                string
+            } else {
+                atoms.UTF8_STRING
+            },
+            format: 8,
+            data: text.to_vec(),
+            pasted: true,
+        })
+    } else {
+        None
+    }
+}
+
+fn clipboard_kinds(selection: ClipSelection) -> Vec<LinuxClipboardKind> {
+    match selection {
+        ClipSelection::Both => vec![LinuxClipboardKind::Primary, LinuxClipboardKind::Clipboard],
+        ClipSelection::Clipboard => vec![LinuxClipboardKind::Clipboard],
+        ClipSelection::Primary => vec![LinuxClipboardKind::Primary],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reply_selection_requests() {
+        let atoms = Atoms {
+            CLIPBOARD: 100,
+            TARGETS: 101,
+            TEXT: 102,
+            UTF8_STRING: 103,
+            PASSWORD_MANAGER_HINT: 104,
+        };
+        let targets = selection_reply(&atoms, atoms.TARGETS, b"pw").unwrap();
+        assert_eq!(targets.data.len(), 5 * 4);
+        assert!(!targets.pasted);
+        let hint = selection_reply(&atoms, atoms.PASSWORD_MANAGER_HINT, b"pw").unwrap();
+        assert_eq!(hint.data, b"secret");
+        assert!(!hint.pasted);
+        let text = selection_reply(&atoms, atoms.UTF8_STRING, b"pw").unwrap();
+        assert_eq!(text.data, b"pw");
+        assert!(text.pasted);
+        assert!(selection_reply(&atoms, 200, b"pw").is_none());
     }
-    Ok(())
 }
@@ -2,16 +2,24 @@ use std::env::temp_dir;
 use std::io::{BufWriter, Write};
 
 use anyhow::Error;
+use rand::thread_rng;
 
+use crate::cmd::edit::edit_entry;
+use crate::entry::{Entry, EntryType};
 use crate::env::AikotEnv;
 use crate::err::AikotError;
 use crate::gpg::encrypt;
-use crate::io::{open_editor, read_file};
-use crate::password::PwGen;
+use crate::password::{PwGen, PwGenOptions};
 use crate::tempfile::create_temp_file;
 use crate::template::PassTmpl;
+use crate::username::Username;
 
-pub fn cmd_add(aikot_env: &AikotEnv, name: &str, opwgen: Option<&PwGen>) -> Result<(), Error> {
+pub fn cmd_add(
+    aikot_env: &AikotEnv,
+    name: &str,
+    pwgen_opts: Option<&PwGenOptions>,
+    entry_type: Option<EntryType>,
+) -> Result<(), Error> {
     let pass_file = aikot_env.password_store_file(name)?;
     if pass_file.exists() {
         return Err(AikotError::PassAlreadyExists {
@@ -24,13 +32,35 @@ pub fn cmd_add(aikot_env: &AikotEnv, name: &str, opwgen: Option<&PwGen>) -> Resu
 
     let tmpl_path = aikot_env.template_file();
     let mut ptmpl = PassTmpl::new();
-    if tmpl_path.is_file() {
+    if let Some(entry_type) = entry_type {
+        ptmpl.load_type(entry_type)?;
+    } else if tmpl_path.is_file() {
         ptmpl.load(tmpl_path)?;
     } else {
         ptmpl.load_default()?;
     }
-    let pass = if let Some(pwgen) = opwgen {
-        pwgen.try_generate()?
+    let mut rng = thread_rng();
+    ptmpl.set_var(
+        "Username",
+        &Username::Words(2, "_".to_string()).generate(&mut rng),
+    );
+    // A malformed email_alias only matters to templates using it.
+    if ptmpl.uses_var("EmailAlias") {
+        if let Some(alias) = aikot_env.email_alias()? {
+            ptmpl.set_var("EmailAlias", &alias.generate(&mut rng));
+        }
+    }
+    let pass = if let Some(opts) = pwgen_opts {
+        let mut opts = opts.clone();
+        opts.default_rules(
+            Entry::parse(&ptmpl.render("", name)?)
+                .field("rules")
+                .filter(|rules| !rules.is_empty()),
+        );
+        if opts.uses_configured_charset() {
+            opts.charset = aikot_env.character_set(opts.symbol)?;
+        }
+        PwGen::from_options(&opts)?.try_generate()?
     } else {
         "".to_string()
     };
@@ -39,9 +69,7 @@ pub fn cmd_add(aikot_env: &AikotEnv, name: &str, opwgen: Option<&PwGen>) -> Resu
     write!(buf_write, "{}", ptmpl.render(&pass, name)?)?;
     drop(buf_write);
 
-    open_editor(temp_path.as_ref())?;
-
-    let new_contents = read_file(temp_path.as_ref())?;
+    let new_contents = edit_entry(temp_path.as_ref())?;
     if new_contents.is_empty() {
         return Err(AikotError::EmptyPassword {
             name: name.to_string(),
@@ -0,0 +1,106 @@
+use std::panic::resume_unwind;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread::{self, available_parallelism};
+
+use anyhow::Error;
+
+use crate::audit::{audit, AuditOptions};
+use crate::cmd::list::secret_names;
+use crate::entry::Entry;
+use crate::env::AikotEnv;
+use crate::err::AikotError;
+use crate::gpg::decrypt;
+use crate::hibp::PwnedFile;
+
+pub fn cmd_audit(
+    aikot_env: &AikotEnv,
+    opts: &AuditOptions,
+    max_findings: usize,
+    hibp_file: Option<&Path>,
+) -> Result<(), Error> {
+    let mut pwned = hibp_file.map(PwnedFile::open).transpose()?;
+    let names = secret_names(aikot_env.base_dir())?;
+    let mut count = 0;
+    let findings = decrypt_each(aikot_env, &names, |entries| {
+        audit(
+            entries.into_iter().inspect(|_| count += 1),
+            opts,
+            pwned.as_mut(),
+        )
+    })?;
+    for finding in findings.iter() {
+        println!("{}", finding);
+    }
+    eprintln!("Audited {} secrets, {} findings", count, findings.len());
+    if findings.len() > max_findings {
+        return Err(AikotError::AuditFail {
+            count: findings.len(),
+            max: max_findings,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+// Run as many gpg processes as cpus, passing entries to check as soon as they are decrypted
+// and skipping binary secrets which have no password.
+fn decrypt_each<T, F>(aikot_env: &AikotEnv, names: &[String], check: F) -> Result<T, Error>
+where
+    F: FnOnce(Receiver<(String, Entry)>) -> Result<T, Error>,
+{
+    let next = AtomicUsize::new(0);
+    let workers = available_parallelism().map_or(1, |n| n.get());
+    // Bounded, so that decrypted entries do not pile up waiting for their checks.
+    let (sender, receiver) = sync_channel(workers);
+    thread::scope(|s| {
+        let handles = (0..workers)
+            .map(|_| {
+                let sender = sender.clone();
+                let next = &next;
+                s.spawn(move || {
+                    while let Some(name) = names.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let contents = aikot_env
+                            .password_store_file(name)
+                            .and_then(|file| decrypt(aikot_env, file));
+                        match contents {
+                            Ok(contents) => {
+                                // The receiver is gone when the checks failed.
+                                if sender
+                                    .send((name.clone(), Entry::parse(&contents)))
+                                    .is_err()
+                                {
+                                    break;
+                                }
+                            }
+                            Err(err) if is_binary(&err) => {
+                                eprintln!("Skipped binary secret: {}", name)
+                            }
+                            Err(err) => {
+                                // Stop the other workers too.
+                                next.store(names.len(), Ordering::Relaxed);
+                                return Err(err);
+                            }
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(sender);
+        let checked = check(receiver);
+        next.store(names.len(), Ordering::Relaxed);
+        for handle in handles {
+            handle.join().unwrap_or_else(|err| resume_unwind(err))?;
+        }
+        checked
+    })
+}
+
+fn is_binary(err: &Error) -> bool {
+    matches!(
+        err.downcast_ref::<AikotError>(),
+        Some(AikotError::BinaryPassword { .. })
+    )
+}
@@ -0,0 +1,46 @@
+use anyhow::Error;
+
+use crate::autotype::{parse_sequence, Resolved, Token, Typer, DEFAULT_SEQUENCE};
+use crate::entry::Entry;
+use crate::env::AikotEnv;
+use crate::err::AikotError;
+use crate::gpg::decrypt;
+
+pub fn cmd_type(aikot_env: &AikotEnv, name: &str, typer: Option<&str>) -> Result<(), Error> {
+    let file = aikot_env.password_store_file(name)?;
+    if !file.is_file() {
+        return Err(AikotError::PassNotFound {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    let entry = Entry::parse(&decrypt(aikot_env, &file)?);
+    let seq = entry.field("autotype").unwrap_or(DEFAULT_SEQUENCE);
+
+    // Resolve all fields before typing anything.
+    let mut tokens = vec![];
+    for token in parse_sequence(seq)? {
+        tokens.push(match token {
+            Token::Field(field) => {
+                let val = entry
+                    .value(&field)
+                    .ok_or_else(|| AikotError::FieldNotFound {
+                        name: name.to_string(),
+                        field: field.to_string(),
+                    })?;
+                Resolved::Text(val.to_string())
+            }
+            Token::Key(key) => Resolved::Key(key),
+            Token::Text(text) => Resolved::Text(text),
+        });
+    }
+
+    let typer = Typer::new(typer.or_else(|| aikot_env.config().get("typer")));
+    for token in tokens {
+        match token {
+            Resolved::Key(key) => typer.press_key(key)?,
+            Resolved::Text(text) => typer.type_text(&text)?,
+        }
+    }
+    Ok(())
+}
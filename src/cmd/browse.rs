@@ -1,27 +1,83 @@
+use std::io::{stderr, stdin, Write};
+
 use anyhow::Error;
 
-use crate::browser::browser_command;
+use crate::browser::open_url;
+use crate::clipboard::ClipOptions;
+use crate::cmd::clip_password;
+use crate::entry::{url_from_name, Entry};
 use crate::env::AikotEnv;
 use crate::err::AikotError;
 use crate::gpg::decrypt;
 
-pub fn cmd_browse(aikot_env: &AikotEnv, name: &str) -> Result<(), Error> {
+pub fn cmd_browse(
+    aikot_env: &AikotEnv,
+    name: &str,
+    index: Option<usize>,
+    print: bool,
+    clip_opts: Option<&ClipOptions>,
+) -> Result<(), Error> {
     let file = aikot_env.password_store_file(name)?;
-    if file.is_file() {
-        let contents = decrypt(aikot_env, &file)?;
-        for line in contents.lines().skip(1) {
-            if let Some(val) = line.strip_prefix("url:") {
-                let url = val.trim();
-                browser_command().arg(url).spawn()?.wait()?;
-                return Ok(());
+    if !file.is_file() {
+        return Err(AikotError::PassNotFound {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    let entry = Entry::parse(&decrypt(aikot_env, &file)?);
+    let url = find_url(&entry, name, index)?;
+    if print {
+        println!("{}", url);
+    } else {
+        open_url(aikot_env.config(), &url, clip_opts.is_none())?;
+    }
+    if let Some(opts) = clip_opts {
+        clip_password(name, entry.password(), opts)?;
+    }
+    Ok(())
+}
+
+fn find_url(entry: &Entry, name: &str, index: Option<usize>) -> Result<String, Error> {
+    let mut urls = entry.urls();
+    if urls.is_empty() {
+        if let Some(url) = url_from_name(name) {
+            urls.push(url);
+        } else {
+            return Err(AikotError::UrlNotFound {
+                name: name.to_string(),
             }
+            .into());
         }
-        Err(AikotError::UrlNotFound {
-            name: name.to_string(),
-        }.into())
+    }
+    let index = if let Some(index) = index {
+        index
+    } else if urls.len() == 1 {
+        1
     } else {
-        Err(AikotError::PassNotFound {
-            name: name.to_string(),
-        }.into())
+        choose_url(&urls)?
+    };
+    if index == 0 || index > urls.len() {
+        return Err(AikotError::InvalidIndex {
+            index: index.to_string(),
+        }
+        .into());
     }
+    Ok(urls.swap_remove(index - 1))
+}
+
+fn choose_url(urls: &[String]) -> Result<usize, Error> {
+    let mut err = stderr();
+    for (i, url) in urls.iter().enumerate() {
+        writeln!(err, "{}) {}", i + 1, url)?;
+    }
+    write!(err, "Select url [1-{}]: ", urls.len())?;
+    err.flush()?;
+    let mut line = String::new();
+    stdin().read_line(&mut line)?;
+    line.trim().parse().map_err(|_| {
+        AikotError::InvalidIndex {
+            index: line.trim().to_string(),
+        }
+        .into()
+    })
 }
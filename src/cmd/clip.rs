@@ -1,24 +1,18 @@
 use anyhow::Error;
 
-#[cfg(windows)]
+#[cfg(any(windows, target_os = "macos"))]
 use crate::clipboard::clear_clip;
-use crate::clipboard::set_clip;
+use crate::clipboard::{set_clip, set_clip_sequence, ClipBackend, ClipOptions, ClipSelection};
+use crate::entry::Entry;
 use crate::env::AikotEnv;
 use crate::err::AikotError;
 use crate::gpg::decrypt;
 
-pub fn cmd_clip(aikot_env: &AikotEnv, name: &str) -> Result<(), Error> {
+pub fn cmd_clip(aikot_env: &AikotEnv, name: &str, opts: &ClipOptions) -> Result<(), Error> {
     let file = aikot_env.password_store_file(name)?;
     if file.is_file() {
         let contents = decrypt(aikot_env, &file)?;
-        if let Some(pass) = contents.lines().next() {
-            set_clip(pass)
-        } else {
-            Err(AikotError::EmptyPassword {
-                name: name.to_string(),
-            }
-            .into())
-        }
+        clip_password(name, contents.lines().next().unwrap_or_default(), opts)
     } else {
         Err(AikotError::PassNotFound {
             name: name.to_string(),
@@ -27,7 +21,97 @@ pub fn cmd_clip(aikot_env: &AikotEnv, name: &str) -> Result<(), Error> {
     }
 }
 
-#[cfg(windows)]
-pub fn cmd_unclip(_aikot_env: &AikotEnv) -> Result<(), Error> {
-    clear_clip()
+pub fn clip_password(name: &str, pass: &str, opts: &ClipOptions) -> Result<(), Error> {
+    if pass.is_empty() {
+        return Err(AikotError::EmptyPassword {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    print_copied(name, opts.timeout);
+    set_clip(pass, opts)
+}
+
+pub fn cmd_clip_sequence(
+    aikot_env: &AikotEnv,
+    name: &str,
+    fields: &[&str],
+    opts: &ClipOptions,
+) -> Result<(), Error> {
+    let file = aikot_env.password_store_file(name)?;
+    if !file.is_file() {
+        return Err(AikotError::PassNotFound {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    let entry = Entry::parse(&decrypt(aikot_env, &file)?);
+    let mut texts = vec![];
+    for field in fields {
+        texts.push(
+            entry
+                .value(field)
+                .ok_or_else(|| AikotError::FieldNotFound {
+                    name: name.to_string(),
+                    field: field.to_string(),
+                })?,
+        );
+    }
+
+    let label = |i: usize| format!("{} of {}", fields[i], name);
+    set_clip_sequence(&texts, opts, &mut |i| {
+        if i + 1 < fields.len() {
+            println!(
+                "Copied {} to clipboard. Press Enter to copy {}.",
+                label(i),
+                fields[i + 1]
+            );
+        } else {
+            print_copied(&label(i), opts.timeout);
+        }
+    })
+}
+
+fn print_copied(label: &str, timeout: u64) {
+    if timeout == 0 {
+        println!("Copied {} to clipboard.", label);
+    } else {
+        println!(
+            "Copied {} to clipboard. Will clear in {} seconds.",
+            label, timeout
+        );
+    }
+}
+
+pub fn clip_options(
+    aikot_env: &AikotEnv,
+    backend: ClipBackend,
+    selection: Option<ClipSelection>,
+    timeout: Option<u64>,
+) -> Result<ClipOptions, Error> {
+    let selection = if let Some(selection) = selection {
+        selection
+    } else {
+        aikot_env.x_selection()?
+    };
+    let timeout = if let Some(timeout) = timeout {
+        timeout
+    } else {
+        aikot_env.clip_time()?
+    };
+    Ok(ClipOptions {
+        backend,
+        selection,
+        timeout,
+    })
+}
+
+#[cfg(any(windows, target_os = "macos"))]
+pub fn cmd_unclip(aikot_env: &AikotEnv, timeout: Option<u64>) -> Result<(), Error> {
+    let timeout = if let Some(timeout) = timeout {
+        timeout
+    } else {
+        aikot_env.clip_time()?
+    };
+    clear_clip(timeout)
 }
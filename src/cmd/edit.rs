@@ -1,9 +1,11 @@
-use std::io::{BufWriter, Write};
+use std::io::{stderr, stdin, BufWriter, Write};
+use std::path::Path;
 
 use std::env::temp_dir;
 
 use anyhow::Error;
 
+use crate::entry::Entry;
 use crate::env::AikotEnv;
 use crate::gpg::{decrypt, encrypt};
 use crate::io::{open_editor, read_file};
@@ -19,12 +21,34 @@ pub fn cmd_edit(aikot_env: &AikotEnv, name: &str) -> Result<(), Error> {
     buf_write.write_all(contents.as_bytes())?;
     drop(buf_write);
 
-    open_editor(temp_path.as_ref())?;
-
-    let new_contents = read_file(temp_path.as_ref())?;
+    let new_contents = edit_entry(temp_path.as_ref())?;
     if contents == new_contents {
         println!("{} unchanged", name);
         return Ok(());
     }
     encrypt(aikot_env, &pass_file, &new_contents)
 }
+
+// Reopen the editor on the same file until the entry is valid, printing only the reason
+// so that the secret does not leak to the terminal.
+pub fn edit_entry(path: &Path) -> Result<String, Error> {
+    loop {
+        open_editor(path)?;
+        let contents = read_file(path)?;
+        let err = match Entry::parse(&contents).validate() {
+            Ok(()) => return Ok(contents),
+            Err(err) => err,
+        };
+        let mut out = stderr();
+        write!(out, "{}\nEdit again? [Y/n] ", err)?;
+        out.flush()?;
+        let mut line = String::new();
+        if stdin().read_line(&mut line)? == 0 {
+            writeln!(out)?;
+            return Err(err);
+        }
+        if line.trim().eq_ignore_ascii_case("n") {
+            return Err(err);
+        }
+    }
+}
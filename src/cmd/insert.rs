@@ -0,0 +1,33 @@
+use std::io::{stdin, Read};
+use std::path::Path;
+
+use anyhow::Error;
+
+use crate::env::AikotEnv;
+use crate::err::AikotError;
+use crate::gpg::encrypt_bytes;
+use crate::io::read_file_bytes;
+
+pub fn cmd_insert(aikot_env: &AikotEnv, name: &str, file: Option<&Path>) -> Result<(), Error> {
+    let pass_file = aikot_env.password_store_file(name)?;
+    if pass_file.exists() {
+        return Err(AikotError::PassAlreadyExists {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    let contents = if let Some(path) = file {
+        read_file_bytes(path)?
+    } else {
+        let mut buffer = Vec::new();
+        stdin().read_to_end(&mut buffer)?;
+        buffer
+    };
+    if contents.is_empty() {
+        return Err(AikotError::EmptyPassword {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    encrypt_bytes(aikot_env, &pass_file, &contents)
+}
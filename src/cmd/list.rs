@@ -5,10 +5,25 @@ use anyhow::Error;
 use crate::env::AikotEnv;
 
 pub fn cmd_list(aikot_env: &AikotEnv, pattern: Option<&str>) -> Result<(), Error> {
-    list_dir(aikot_env.base_dir(), pattern, None)
+    for secret in secret_names(aikot_env.base_dir())? {
+        if pattern.is_none() || secret.contains(pattern.unwrap()) {
+            println!("{}", secret);
+        }
+    }
+    Ok(())
+}
+
+// Names of all secrets under the directory, in the order of read_dir.
+pub fn secret_names<P>(dir: P) -> Result<Vec<String>, Error>
+where
+    P: AsRef<Path>,
+{
+    let mut secrets = Vec::new();
+    list_dir(dir, None, &mut secrets)?;
+    Ok(secrets)
 }
 
-fn list_dir<P>(dir: P, pattern: Option<&str>, prefix_opt: Option<&PathBuf>) -> Result<(), Error>
+fn list_dir<P>(dir: P, prefix_opt: Option<&PathBuf>, secrets: &mut Vec<String>) -> Result<(), Error>
 where
     P: AsRef<Path>,
 {
@@ -22,7 +37,7 @@ where
                     PathBuf::new()
                 };
                 pbuf.push(p);
-                list_dir(&path, pattern, Some(&pbuf))?;
+                list_dir(&path, Some(&pbuf), secrets)?;
             }
         } else if let Some(ext) = path.extension() {
             if ext == "gpg" {
@@ -32,9 +47,7 @@ where
                     } else {
                         format!("{}", name.to_string_lossy())
                     };
-                    if pattern.is_none() || secret.contains(pattern.unwrap()) {
-                        println!("{}", secret);
-                    }
+                    secrets.push(secret);
                 }
             }
         }
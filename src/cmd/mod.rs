@@ -1,19 +1,29 @@
 mod add;
+mod audit;
+mod autotype;
 mod browse;
 mod clip;
 mod completion;
 mod edit;
 mod init;
+mod insert;
 mod list;
+mod otp;
 mod pwgen;
 mod show;
+mod strength;
 
 pub use add::cmd_add;
+pub use audit::cmd_audit;
+pub use autotype::cmd_type;
 pub use browse::cmd_browse;
 pub use clip::*;
 pub use completion::cmd_completion;
 pub use edit::cmd_edit;
 pub use init::cmd_init;
+pub use insert::cmd_insert;
 pub use list::cmd_list;
-pub use pwgen::cmd_pwgen;
-pub use show::cmd_show;
+pub use otp::{cmd_otp, cmd_otp_import, cmd_otp_insert};
+pub use pwgen::{cmd_pwgen, cmd_pwgen_email_alias, cmd_pwgen_username};
+pub use show::{cmd_show, cmd_show_qrcode, cmd_show_raw};
+pub use strength::cmd_check_strength;
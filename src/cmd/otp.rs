@@ -0,0 +1,89 @@
+use std::io::{stderr, stdin, Write};
+use std::path::Path;
+
+use anyhow::Error;
+
+use crate::clipboard::ClipOptions;
+use crate::cmd::clip_password;
+use crate::env::AikotEnv;
+use crate::err::AikotError;
+use crate::gpg::{decrypt, encrypt};
+use crate::otp::{append_uri, find_uri, with_counter, Otp, OtpKind};
+use crate::qr::decode_image;
+
+pub fn cmd_otp(
+    aikot_env: &AikotEnv,
+    name: &str,
+    clip_opts: Option<&ClipOptions>,
+) -> Result<(), Error> {
+    let file = aikot_env.password_store_file(name)?;
+    if !file.is_file() {
+        return Err(AikotError::PassNotFound {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    let contents = decrypt(aikot_env, &file)?;
+    let uri = find_uri(&contents).ok_or_else(|| AikotError::OtpNotFound {
+        name: name.to_string(),
+    })?;
+    let otp = Otp::parse(uri)?;
+    let code = otp.code()?;
+    // The counter is saved before the code is used, so that a code is never reused.
+    if let OtpKind::Hotp { counter } = otp.kind() {
+        let new_contents = contents.replacen(uri, &with_counter(uri, counter + 1), 1);
+        encrypt(aikot_env, &file, &new_contents)?;
+    }
+    if let Some(opts) = clip_opts {
+        clip_password(name, &code, opts)
+    } else {
+        println!("{}", code);
+        Ok(())
+    }
+}
+
+pub fn cmd_otp_import(aikot_env: &AikotEnv, name: &str, qr: &Path) -> Result<(), Error> {
+    let file = aikot_env.password_store_file(name)?;
+    if !file.is_file() {
+        return Err(AikotError::PassNotFound {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    let contents = decode_image(qr)?;
+    let uri = contents
+        .iter()
+        .find_map(|content| find_uri(content))
+        .ok_or_else(|| AikotError::InvalidOtpUri {
+            reason: "qr code does not contain otpauth uri".to_string(),
+        })?;
+    Otp::parse(uri)?;
+    save_uri(aikot_env, name, &file, uri)
+}
+
+pub fn cmd_otp_insert(aikot_env: &AikotEnv, name: &str) -> Result<(), Error> {
+    let file = aikot_env.password_store_file(name)?;
+    if !file.is_file() {
+        return Err(AikotError::PassNotFound {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    let mut err = stderr();
+    write!(err, "Enter otpauth URI for {}: ", name)?;
+    err.flush()?;
+    let mut line = String::new();
+    stdin().read_line(&mut line)?;
+    let uri = line.trim();
+    Otp::parse(uri)?;
+    save_uri(aikot_env, name, &file, uri)
+}
+
+// Append the otpauth URI to the end of the secret, keeping an existing one untouched.
+fn save_uri(aikot_env: &AikotEnv, name: &str, file: &Path, uri: &str) -> Result<(), Error> {
+    let contents = decrypt(aikot_env, file)?;
+    let new_contents = append_uri(&contents, uri).ok_or_else(|| AikotError::OtpAlreadyExists {
+        name: name.to_string(),
+    })?;
+    encrypt(aikot_env, file, &new_contents)
+}
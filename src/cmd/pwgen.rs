@@ -1,11 +1,47 @@
 use anyhow::Error;
+use rand::thread_rng;
 
 use crate::env::AikotEnv;
+use crate::err::AikotError;
 use crate::password::PwGen;
+use crate::username::Username;
 
-pub fn cmd_pwgen(_aikot_env: &AikotEnv, pwgen: &PwGen, count: u16) -> Result<(), Error> {
+pub fn cmd_pwgen(
+    _aikot_env: &AikotEnv,
+    pwgen: &PwGen,
+    count: u16,
+    show_entropy: bool,
+) -> Result<(), Error> {
     for _i in 0..count {
         println!("{}", pwgen.try_generate()?);
     }
+    if show_entropy {
+        eprintln!("Entropy: {:.1} bits", pwgen.entropy());
+    }
+    Ok(())
+}
+
+pub fn cmd_pwgen_username(
+    _aikot_env: &AikotEnv,
+    username: &Username,
+    count: u16,
+) -> Result<(), Error> {
+    let mut rng = thread_rng();
+    for _i in 0..count {
+        println!("{}", username.generate(&mut rng));
+    }
+    Ok(())
+}
+
+pub fn cmd_pwgen_email_alias(aikot_env: &AikotEnv, count: u16) -> Result<(), Error> {
+    let alias = aikot_env
+        .email_alias()?
+        .ok_or_else(|| AikotError::ConfigNotFound {
+            key: "email_alias".to_string(),
+        })?;
+    let mut rng = thread_rng();
+    for _i in 0..count {
+        println!("{}", alias.generate(&mut rng));
+    }
     Ok(())
 }
@@ -1,8 +1,14 @@
+use std::io::{stdout, Write};
+use std::path::Path;
+
 use anyhow::Error;
 
+use crate::entry::Entry;
 use crate::env::AikotEnv;
 use crate::err::AikotError;
-use crate::gpg::decrypt;
+use crate::gpg::{decrypt, decrypt_bytes};
+use crate::io::create_private_file;
+use crate::qr::{render_terminal, write_image};
 
 pub fn cmd_show(aikot_env: &AikotEnv, name: &str) -> Result<(), Error> {
     let file = aikot_env.password_store_file(name)?;
@@ -18,3 +24,59 @@ pub fn cmd_show(aikot_env: &AikotEnv, name: &str) -> Result<(), Error> {
         }.into())
     }
 }
+
+pub fn cmd_show_raw(aikot_env: &AikotEnv, name: &str, output: Option<&Path>) -> Result<(), Error> {
+    let file = aikot_env.password_store_file(name)?;
+    if file.is_file() {
+        let contents = decrypt_bytes(aikot_env, &file)?;
+        if let Some(path) = output {
+            create_private_file(path)?.write_all(&contents)?;
+        } else {
+            let mut out = stdout().lock();
+            out.write_all(&contents)?;
+            out.flush()?;
+        }
+        Ok(())
+    } else {
+        Err(AikotError::PassNotFound {
+            name: name.to_string(),
+        }.into())
+    }
+}
+
+pub fn cmd_show_qrcode(
+    aikot_env: &AikotEnv,
+    name: &str,
+    field: Option<&str>,
+    output: Option<&Path>,
+) -> Result<(), Error> {
+    let file = aikot_env.password_store_file(name)?;
+    if !file.is_file() {
+        return Err(AikotError::PassNotFound {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    let entry = Entry::parse(&decrypt(aikot_env, &file)?);
+    // Typed entries like Wi-Fi have their own payload, e.g. for joining the network.
+    let value = match (field, entry.qr_payload()) {
+        (None, Some(payload)) => payload,
+        _ => {
+            let field = field.unwrap_or("password");
+            entry
+                .value(field)
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| AikotError::FieldNotFound {
+                    name: name.to_string(),
+                    field: field.to_string(),
+                })?
+                .to_string()
+        }
+    };
+    if let Some(path) = output {
+        write_image(&value, path)
+    } else {
+        println!("{}", render_terminal(&value)?);
+        Ok(())
+    }
+}
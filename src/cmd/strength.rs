@@ -0,0 +1,32 @@
+use anyhow::Error;
+
+use crate::entry::Entry;
+use crate::env::AikotEnv;
+use crate::err::AikotError;
+use crate::gpg::decrypt;
+use crate::strength::estimate;
+
+pub fn cmd_check_strength(aikot_env: &AikotEnv, name: &str) -> Result<(), Error> {
+    let file = aikot_env.password_store_file(name)?;
+    if !file.is_file() {
+        return Err(AikotError::PassNotFound {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    let entry = Entry::parse(&decrypt(aikot_env, &file)?);
+    if entry.password().is_empty() {
+        return Err(AikotError::EmptyPassword {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    let strength = estimate(entry.password());
+    println!("Length: {}", entry.password().chars().count());
+    println!("Entropy: {:.1} bits", strength.entropy);
+    println!("Score: {}/4 ({})", strength.score(), strength.label());
+    for m in strength.matches.iter() {
+        println!("Found {} at {}-{}", m.pattern, m.start + 1, m.end);
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Error;
+
+use crate::io::read_file;
+
+#[derive(Default)]
+pub struct Config {
+    values: HashMap<String, String>,
+}
+
+impl Config {
+    pub fn load<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        if path.as_ref().is_file() {
+            Ok(Config::parse(&read_file(path.as_ref())?))
+        } else {
+            Ok(Config::default())
+        }
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let mut values = HashMap::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, val)) = line.split_once('=') {
+                values.insert(key.trim().to_string(), val.trim().to_string());
+            }
+        }
+        Config { values }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|s| s.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_key_values() {
+        let config = Config::parse("clip_time = 10\n# comment\n\nbrowser=firefox -P work\n");
+        assert_eq!(config.get("clip_time"), Some("10"));
+        assert_eq!(config.get("browser"), Some("firefox -P work"));
+        assert_eq!(config.get("comment"), None);
+    }
+
+    #[test]
+    fn parse_ignores_invalid_lines() {
+        let config = Config::parse("clip_time\n[section]\n");
+        assert_eq!(config.get("clip_time"), None);
+        assert_eq!(config.get("[section]"), None);
+    }
+
+    #[test]
+    fn load_when_file_not_found() {
+        let config = Config::load("/no/such/aikot/config").unwrap();
+        assert_eq!(config.get("clip_time"), None);
+    }
+}
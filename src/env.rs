@@ -4,24 +4,39 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Error;
 
+use crate::clipboard::ClipSelection;
+use crate::config::Config;
 use crate::err::AikotError;
 use crate::io::read_file;
+use crate::username::EmailAlias;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum ShellType {
     Bash,
 }
 
+const DEFAULT_CLIP_TIME: u64 = 45;
+
 pub struct AikotEnv {
     base_dir: PathBuf,
     gpg_path: PathBuf,
+    config: Config,
 }
 
 impl AikotEnv {
     pub fn from_env() -> Result<Self, Error> {
         let base_dir = password_store_dir()?;
         let gpg_path = gpg_path()?;
-        Ok(AikotEnv { base_dir, gpg_path })
+        let config = if let Some(path) = config_file() {
+            Config::load(path)?
+        } else {
+            Config::default()
+        };
+        Ok(AikotEnv {
+            base_dir,
+            gpg_path,
+            config,
+        })
     }
 
     pub fn base_dir(&self) -> &Path {
@@ -38,6 +53,55 @@ impl AikotEnv {
         &self.gpg_path
     }
 
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn clip_time(&self) -> Result<u64, Error> {
+        clip_time(&self.config)
+    }
+
+    pub fn x_selection(&self) -> Result<ClipSelection, Error> {
+        if let Some(val) = env::var_os("PASSWORD_STORE_X_SELECTION") {
+            val.to_str().and_then(|s| s.parse().ok()).ok_or_else(|| {
+                AikotError::InvalidEnv {
+                    name: "PASSWORD_STORE_X_SELECTION".to_string(),
+                }
+                .into()
+            })
+        } else {
+            Ok(ClipSelection::Clipboard)
+        }
+    }
+
+    // Character set for generated passwords as PASSWORD_STORE_CHARACTER_SET of pass.
+    pub fn character_set(&self, symbol: bool) -> Result<Option<String>, Error> {
+        let name = if symbol {
+            "PASSWORD_STORE_CHARACTER_SET"
+        } else {
+            "PASSWORD_STORE_CHARACTER_SET_NO_SYMBOLS"
+        };
+        env::var_os(name)
+            .map(|val| {
+                val.into_string().map_err(|_| {
+                    AikotError::InvalidEnv {
+                        name: name.to_string(),
+                    }
+                    .into()
+                })
+            })
+            .transpose()
+    }
+
+    // Base address of generated email aliases.
+    pub fn email_alias(&self) -> Result<Option<EmailAlias>, Error> {
+        Ok(self
+            .config
+            .get("email_alias")
+            .map(|base| base.parse())
+            .transpose()?)
+    }
+
     pub fn get_recipients(&self) -> Result<Vec<String>, Error> {
         let path = self.gpg_id_path();
         if path.is_file() {
@@ -84,6 +148,42 @@ pub fn editor_cmd() -> Result<OsString, Error> {
     }
 }
 
+fn clip_time(config: &Config) -> Result<u64, Error> {
+    if let Some(val) = env::var_os("PASSWORD_STORE_CLIP_TIME") {
+        val.to_str().and_then(|s| s.parse().ok()).ok_or_else(|| {
+            AikotError::InvalidEnv {
+                name: "PASSWORD_STORE_CLIP_TIME".to_string(),
+            }
+            .into()
+        })
+    } else if let Some(val) = config.get("clip_time") {
+        val.parse().map_err(|_| {
+            AikotError::InvalidConfig {
+                key: "clip_time".to_string(),
+            }
+            .into()
+        })
+    } else {
+        Ok(DEFAULT_CLIP_TIME)
+    }
+}
+
+fn config_file() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("AIKOT_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let mut pbuf = if let Some(dir) = env::var_os("XDG_CONFIG_HOME") {
+        PathBuf::from(dir)
+    } else {
+        let mut pbuf = PathBuf::from(env::var_os("HOME")?);
+        pbuf.push(".config");
+        pbuf
+    };
+    pbuf.push("aikot");
+    pbuf.push("config");
+    Some(pbuf)
+}
+
 fn password_store_dir() -> Result<PathBuf, Error> {
     if let Some(dir) = env::var_os("PASSWORD_STORE_DIR") {
         Ok(PathBuf::from(dir))
@@ -98,7 +198,7 @@ fn password_store_dir() -> Result<PathBuf, Error> {
     }
 }
 
-fn find_executable(name: &str) -> Option<PathBuf> {
+pub fn find_executable(name: &str) -> Option<PathBuf> {
     if let Some(paths) = env::var_os("PATH") {
         for path in env::split_paths(&paths) {
             let mut pb = path.to_path_buf();
@@ -177,6 +277,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn clip_time_precedence() {
+        let config = Config::parse("clip_time = 10\n");
+        env::remove_var("PASSWORD_STORE_CLIP_TIME");
+        assert_eq!(clip_time(&Config::default()).unwrap(), 45);
+        assert_eq!(clip_time(&config).unwrap(), 10);
+        env::set_var("PASSWORD_STORE_CLIP_TIME", "0");
+        assert_eq!(clip_time(&config).unwrap(), 0);
+        env::set_var("PASSWORD_STORE_CLIP_TIME", "ten");
+        assert_eq!(
+            format!("{}", clip_time(&config).unwrap_err()),
+            "invalid environment: PASSWORD_STORE_CLIP_TIME"
+        );
+        env::remove_var("PASSWORD_STORE_CLIP_TIME");
+        assert_eq!(
+            format!(
+                "{}",
+                clip_time(&Config::parse("clip_time = -1")).unwrap_err()
+            ),
+            "invalid config: clip_time"
+        );
+    }
+
     #[cfg(unix)]
     #[test]
     fn find_executable_found() {
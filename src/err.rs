@@ -5,12 +5,33 @@ pub enum AikotError {
     #[error("password store already initialized: {path:}")]
     AlreadyInitialized { path: String },
 
+    #[error("audit found {count:} findings, more than {max:}")]
+    AuditFail { count: usize, max: usize },
+
+    #[error("password file is binary, use show --raw: {path:}")]
+    BinaryPassword { path: String },
+
+    #[error("browser command not found")]
+    BrowserNotFound,
+
     #[error("command execution fail: {stderr:}")]
     CommandFail { stderr: String },
 
+    #[error("command not found: {name:}")]
+    CommandNotFound { name: String },
+
+    #[error("config not found: {key:}")]
+    ConfigNotFound { key: String },
+
+    #[error("conflicting options: {reason:}")]
+    ConflictingOptions { reason: String },
+
     #[error("password file is empty: {name:}")]
     EmptyPassword { name: String },
 
+    #[error("field not found: {field:} in {name:}")]
+    FieldNotFound { name: String, field: String },
+
     #[error("password genaration fail, {pwgen:}")]
     GenerationFail { pwgen: String },
 
@@ -20,21 +41,58 @@ pub enum AikotError {
     #[error("gpg or gpg2 command not found")]
     GpgNotFound,
 
+    #[error("invalid character set: {spec:}")]
+    InvalidCharset { spec: String },
+
+    #[error("invalid config: {key:}")]
+    InvalidConfig { key: String },
+
+    #[error("invalid entry: {reason:}")]
+    InvalidEntry { reason: String },
+
     #[error("invalid environment: {name:}")]
     InvalidEnv { name: String },
 
+    #[error("invalid index: {index:}")]
+    InvalidIndex { index: String },
+
+    #[error("invalid otpauth uri: {reason:}")]
+    InvalidOtpUri { reason: String },
+
+    #[error("invalid password rules: {reason:}")]
+    InvalidPasswordRules { reason: String },
+
+    #[error("invalid pwned passwords file: {path:}")]
+    InvalidPwnedFile { path: String },
+
+    #[error("invalid autotype sequence: {seq:}")]
+    InvalidSequence { seq: String },
+
     #[error("password less than minimum length: {min_len:} > {pwgen:}")]
     MinimumLength { pwgen: String, min_len: usize },
 
+    #[error("otpauth uri already exists: {name:}")]
+    OtpAlreadyExists { name: String },
+
+    #[error("otpauth uri not found: {name:}")]
+    OtpNotFound { name: String },
+
     #[error("password file already exists: {name:}")]
     PassAlreadyExists { name: String },
 
     #[error("password file not found: {name:}")]
     PassNotFound { name: String },
 
+    #[error("qr code not found in image: {path:}")]
+    QrCodeNotFound { path: String },
+
     #[error("recipient not found")]
     RecipientNotFound,
 
+    #[cfg(any(windows, target_os = "macos"))]
+    #[error("unsupported clipboard backend: {backend:}")]
+    UnsupportedBackend { backend: String },
+
     #[error("url field not found: {name:}")]
     UrlNotFound { name: String },
 }
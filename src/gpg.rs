@@ -9,6 +9,19 @@ use crate::env::AikotEnv;
 use crate::err::AikotError;
 
 pub fn decrypt<P>(aikot_env: &AikotEnv, path: P) -> Result<String, Error>
+where
+    P: AsRef<Path>,
+{
+    let bytes = decrypt_bytes(aikot_env, &path)?;
+    String::from_utf8(bytes).map_err(|_| {
+        AikotError::BinaryPassword {
+            path: format!("{}", path.as_ref().display()),
+        }
+        .into()
+    })
+}
+
+pub fn decrypt_bytes<P>(aikot_env: &AikotEnv, path: P) -> Result<Vec<u8>, Error>
 where
     P: AsRef<Path>,
 {
@@ -17,7 +30,7 @@ where
         .arg(path.as_ref())
         .output()?;
     if output.status.success() {
-        Ok(String::from_utf8(output.stdout)?)
+        Ok(output.stdout)
     } else {
         Err(AikotError::CommandFail {
             stderr: String::from_utf8(output.stderr)?,
@@ -29,14 +42,21 @@ pub fn encrypt<P>(aikot_env: &AikotEnv, path: P, contents: &str) -> Result<(), E
 where
     P: AsRef<Path>,
 {
-    let result = encrypt_internal(aikot_env, path, contents);
+    let result = encrypt_internal(aikot_env, path, contents.as_bytes());
     if result.is_err() {
         eprintln!("To-be-encrypted contents:\n-----\n{}-----", contents);
     }
     result
 }
 
-fn encrypt_internal<P>(aikot_env: &AikotEnv, path: P, contents: &str) -> Result<(), Error>
+pub fn encrypt_bytes<P>(aikot_env: &AikotEnv, path: P, contents: &[u8]) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    encrypt_internal(aikot_env, path, contents)
+}
+
+fn encrypt_internal<P>(aikot_env: &AikotEnv, path: P, contents: &[u8]) -> Result<(), Error>
 where
     P: AsRef<Path>,
 {
@@ -56,7 +76,7 @@ where
         cmd.arg("-r").arg(recipient);
     }
     let mut child = cmd.spawn()?;
-    child.stdin.take().unwrap().write_all(contents.as_bytes())?;
+    child.stdin.take().unwrap().write_all(contents)?;
     let output = child.wait_with_output()?;
     if output.status.success() {
         Ok(())
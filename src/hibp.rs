@@ -0,0 +1,141 @@
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use anyhow::Error;
+use data_encoding::HEXUPPER;
+use sha1::{Digest, Sha1};
+
+use crate::err::AikotError;
+
+const HASH_LEN: usize = 40;
+
+// Pwned Passwords file of Have I Been Pwned, lines of "SHA1:COUNT" ordered by hash.
+pub struct PwnedFile {
+    path: PathBuf,
+    reader: BufReader<File>,
+    len: u64,
+}
+
+impl PwnedFile {
+    pub fn open<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(PwnedFile {
+            path: path.as_ref().to_path_buf(),
+            reader: BufReader::new(file),
+            len,
+        })
+    }
+
+    // Number of times the password appeared in breaches, None if not found.
+    pub fn count(&mut self, pass: &str) -> Result<Option<u64>, Error> {
+        let hash = HEXUPPER.encode(&Sha1::digest(pass.as_bytes()));
+        // Bisect byte offsets for the first line whose hash is not less than the target.
+        let mut lo = 0;
+        let mut hi = self.len;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let (start, line) = self.line_from(mid)?;
+            match line {
+                Some(line) if self.compare(&line, &hash)? == Ordering::Less => {
+                    lo = start + line.len() as u64;
+                }
+                _ => hi = mid,
+            }
+        }
+        match self.line_from(lo)?.1 {
+            Some(line) if self.compare(&line, &hash)? == Ordering::Equal => {
+                let count = line[HASH_LEN + 1..].trim();
+                Ok(Some(count.parse().map_err(|_| self.invalid())?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // Read the first line starting at or after the offset, with its start offset.
+    fn line_from(&mut self, offset: u64) -> Result<(u64, Option<String>), Error> {
+        let mut start = offset;
+        if offset > 0 {
+            self.reader.seek(SeekFrom::Start(offset - 1))?;
+            let mut skipped = Vec::new();
+            start += self.reader.read_until(b'\n', &mut skipped)? as u64 - 1;
+        } else {
+            self.reader.seek(SeekFrom::Start(0))?;
+        }
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            Ok((start, None))
+        } else {
+            Ok((start, Some(line)))
+        }
+    }
+
+    fn compare(&self, line: &str, hash: &str) -> Result<Ordering, Error> {
+        match line.get(..HASH_LEN) {
+            Some(line_hash) if line[HASH_LEN..].starts_with(':') => {
+                Ok(line_hash.to_ascii_uppercase().as_str().cmp(hash))
+            }
+            _ => Err(self.invalid()),
+        }
+    }
+
+    fn invalid(&self) -> Error {
+        AikotError::InvalidPwnedFile {
+            path: format!("{}", self.path.display()),
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::io::Write;
+
+    use super::*;
+
+    fn pwned_file(name: &str, lines: &[&str]) -> PwnedFile {
+        let mut path = env::temp_dir();
+        path.push(name);
+        let mut f = File::create(&path).unwrap();
+        for line in lines {
+            write!(f, "{}\r\n", line).unwrap();
+        }
+        drop(f);
+        PwnedFile::open(&path).unwrap()
+    }
+
+    #[test]
+    fn count_pwned_passwords() {
+        // SHA-1 of "123456", "password", "qwerty" and "secret" with dummy hashes around.
+        let mut pwned = pwned_file(
+            "aikot-pwned-passwords.txt",
+            &[
+                "0000000000000000000000000000000000000001:1",
+                "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824",
+                "7C4A8D09CA3762AF61E59520943DC26494F8941B:37359195",
+                "7C4A8D09CA3762AF61E59520943DC26494F8941C:2",
+                "B1B3773A05C0ED0176787A4F1574FF0075F7521E:3912816",
+                "E5E9FA1BA31ECD1AE84F75CAAA474F3A663F05F4:321415",
+            ],
+        );
+        assert_eq!(pwned.count("123456").unwrap(), Some(37359195));
+        assert_eq!(pwned.count("password").unwrap(), Some(9545824));
+        assert_eq!(pwned.count("qwerty").unwrap(), Some(3912816));
+        assert_eq!(pwned.count("secret").unwrap(), Some(321415));
+        assert_eq!(pwned.count("X7#kP2!qL9@zR4$w").unwrap(), None);
+    }
+
+    #[test]
+    fn count_in_empty_or_invalid_file() {
+        let mut pwned = pwned_file("aikot-pwned-empty.txt", &[]);
+        assert_eq!(pwned.count("password").unwrap(), None);
+        let mut pwned = pwned_file("aikot-pwned-invalid.txt", &["password:1"]);
+        assert!(pwned.count("password").is_err());
+    }
+}
@@ -1,5 +1,7 @@
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::Read;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::Path;
 use std::process::Command;
 
@@ -26,3 +28,47 @@ pub fn read_file(path: &Path) -> Result<String, Error> {
     f.read_to_string(&mut buffer)?;
     Ok(buffer)
 }
+
+pub fn read_file_bytes(path: &Path) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+    let mut f = File::open(path)?;
+    f.read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+// Decrypted secrets written out are readable only by the owner.
+pub fn create_private_file(path: &Path) -> Result<File, Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    Ok(options.open(path)?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::remove_file;
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn private_file_round_trip() {
+        let mut path = env::temp_dir();
+        path.push("aikot-private-file.bin");
+        let _ = remove_file(&path);
+        let contents = b"\x00\xff\xfe\x80key\r\n\xc3\x28";
+        create_private_file(&path)
+            .unwrap()
+            .write_all(contents)
+            .unwrap();
+        assert_eq!(read_file_bytes(&path).unwrap(), contents);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = path.metadata().unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+}
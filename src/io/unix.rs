@@ -1,9 +1,12 @@
 use std::ffi::CString;
 use std::fmt;
+use std::process::exit;
 use std::os::unix::ffi::OsStringExt;
+use std::os::unix::io::RawFd;
 use std::path::Path;
+use std::time::Instant;
 
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 
 // Copied from src/util_libc.rs in https://github.com/rust-random/getrandom.
 #[cfg(any(target_os = "solaris", target_os = "illumos"))]
@@ -49,3 +52,57 @@ pub fn create_directory(path: &Path) -> Result<(), Error> {
         Err(UnixError::new("mkdir".to_string()).into())
     }
 }
+
+pub unsafe fn daemonize() -> Result<(), Error> {
+    let child = libc::fork();
+    if child == -1 {
+        return Err(anyhow!("fork() failed"));
+    } else if child > 0 {
+        libc::_exit(0);
+    }
+    let result = libc::setsid();
+    if result == -1 {
+        exit(0);
+    }
+    let dev_null = CString::new("/dev/null")?;
+    let fd = libc::open(dev_null.into_raw(), libc::O_RDWR, 0);
+    if fd != -1 {
+        libc::dup2(fd, libc::STDIN_FILENO);
+        libc::dup2(fd, libc::STDOUT_FILENO);
+        libc::dup2(fd, libc::STDERR_FILENO);
+    }
+    Ok(())
+}
+
+// Wait until one of the descriptors becomes readable or hangs up, returning its index,
+// or None when the deadline passes.
+pub fn poll_readable(fds: &[RawFd], deadline: Option<Instant>) -> Result<Option<usize>, Error> {
+    let mut pollfds = fds
+        .iter()
+        .map(|fd| libc::pollfd {
+            fd: *fd,
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect::<Vec<_>>();
+    loop {
+        let timeout = deadline.map_or(-1, |deadline| {
+            let millis = deadline
+                .saturating_duration_since(Instant::now())
+                .as_millis();
+            libc::c_int::try_from(millis).unwrap_or(libc::c_int::MAX)
+        });
+        let result =
+            unsafe { libc::poll(pollfds.as_mut_ptr(), pollfds.len() as libc::nfds_t, timeout) };
+        if result == -1 {
+            let err = UnixError::new("poll".to_string());
+            if err.errno == libc::EINTR {
+                continue;
+            }
+            return Err(err.into());
+        } else if result == 0 {
+            return Ok(None);
+        }
+        return Ok(pollfds.iter().position(|pollfd| pollfd.revents != 0));
+    }
+}
@@ -1,24 +1,40 @@
-#[cfg(windows)]
+#[cfg(any(windows, target_os = "macos"))]
 use std::env::args;
+use std::path::PathBuf;
+use std::process::exit;
 
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 use argh::{FromArgValue, FromArgs};
 
+mod audit;
+mod autotype;
 mod browser;
 mod clipboard;
 mod cmd;
+mod config;
+mod entry;
 mod env;
 mod err;
 mod gpg;
+mod hibp;
 mod io;
+mod otp;
 mod password;
+mod qr;
 #[cfg(windows)]
 mod rand;
+mod rules;
+mod strength;
 mod template;
 mod tempfile;
+mod username;
 
+use crate::audit::AuditOptions;
+use crate::clipboard::{ClipBackend, ClipSelection};
+use crate::entry::EntryType;
 use crate::env::{AikotEnv, ShellType};
-use crate::password::PwGen;
+use crate::password::{PwGen, PwGenOptions};
+use crate::username::Username;
 
 #[derive(FromArgs, Debug)]
 #[argh(description = "Aikot password manager")]
@@ -31,14 +47,19 @@ struct AikotCommand {
 #[argh(subcommand)]
 enum AikotSubcommand {
     Add(AddCommand),
+    Audit(AuditCommand),
     Browse(BrowseCommand),
+    CheckStrength(CheckStrengthCommand),
     Clip(ClipCommand),
     Completion(CompletionCommand),
     Edit(EditCommand),
     Init(InitCommand),
+    Insert(InsertCommand),
     List(ListCommand),
+    Otp(OtpCommand),
     Pwgen(PwgenCommand),
     Show(ShowCommand),
+    Type(TypeCommand),
     Version(VersionCommand),
 }
 
@@ -56,6 +77,78 @@ struct AddCommand {
 
     #[argh(switch, description = "include symbol characters in password")]
     symbol: bool,
+
+    #[argh(
+        option,
+        long = "type",
+        description = "entry type template, card, ssh or wifi"
+    )]
+    entry_type: Option<EntryType>,
+
+    #[argh(
+        option,
+        description = "site requirements in passwordrules syntax, e.g. \"required: upper; minlength: 12\""
+    )]
+    rules: Option<String>,
+
+    #[argh(
+        option,
+        description = "characters to use in password, e.g. [:alnum:] or a-z0-9"
+    )]
+    charset: Option<String>,
+
+    #[argh(option, description = "characters to exclude from password")]
+    exclude: Option<String>,
+
+    #[argh(switch, description = "exclude ambiguous characters like l1O0")]
+    no_ambiguous: bool,
+
+    #[argh(option, description = "number of words to generate passphrase")]
+    words: Option<usize>,
+
+    #[argh(option, description = "separator between words, default: -")]
+    separator: Option<String>,
+
+    #[argh(switch, description = "capitalize each word of passphrase")]
+    capitalize: bool,
+
+    #[argh(switch, description = "insert a digit into passphrase")]
+    digit: bool,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(
+    subcommand,
+    name = "audit",
+    description = "Report weak, short and reused passwords, and secrets without url"
+)]
+struct AuditCommand {
+    #[argh(
+        option,
+        description = "minimum length of password",
+        default = "12"
+    )]
+    min_length: usize,
+
+    #[argh(
+        option,
+        description = "minimum strength score of password from 0 to 4",
+        default = "3"
+    )]
+    min_score: usize,
+
+    #[argh(
+        option,
+        description = "number of findings allowed before exiting with error",
+        default = "0"
+    )]
+    max_findings: usize,
+
+    #[argh(
+        option,
+        description = "pwned passwords file of Have I Been Pwned, SHA-1 ordered by hash"
+    )]
+    hibp_file: Option<PathBuf>,
 }
 
 #[derive(FromArgs, Debug)]
@@ -63,6 +156,26 @@ struct AddCommand {
 struct BrowseCommand {
     #[argh(positional)]
     name: String,
+
+    #[argh(option, description = "index of url to browse, starting from 1")]
+    index: Option<usize>,
+
+    #[argh(switch, description = "print url instead of opening browser")]
+    print: bool,
+
+    #[argh(switch, description = "copy password to clipboard after opening browser")]
+    clip: bool,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(
+    subcommand,
+    name = "check-strength",
+    description = "Estimate strength of password"
+)]
+struct CheckStrengthCommand {
+    #[argh(positional)]
+    name: String,
 }
 
 #[derive(FromArgs, Debug)]
@@ -70,6 +183,43 @@ struct BrowseCommand {
 struct ClipCommand {
     #[argh(positional)]
     name: String,
+
+    #[argh(
+        option,
+        description = "seconds to clear clipboard after, 0 for never clear"
+    )]
+    timeout: Option<u64>,
+
+    #[argh(
+        option,
+        description = "clipboard backend, auto, osc52, wayland or x11",
+        default = "ClipBackend::Auto"
+    )]
+    backend: ClipBackend,
+
+    #[argh(
+        option,
+        description = "X selection to copy to, clipboard, primary or both"
+    )]
+    selection: Option<ClipSelection>,
+
+    #[argh(
+        option,
+        description = "comma separated fields to copy one by one, e.g. user,password"
+    )]
+    sequence: Option<String>,
+}
+
+impl FromArgValue for ClipBackend {
+    fn from_arg_value(value: &str) -> Result<Self, String> {
+        match value {
+            "auto" => Ok(ClipBackend::Auto),
+            "osc52" => Ok(ClipBackend::Osc52),
+            "wayland" => Ok(ClipBackend::Wayland),
+            "x11" => Ok(ClipBackend::X11),
+            _ => Err(format!("Unknown backend: {}", value)),
+        }
+    }
 }
 
 impl FromArgValue for ShellType {
@@ -111,6 +261,20 @@ struct InitCommand {
     gpg_ids: Vec<String>,
 }
 
+#[derive(FromArgs, Debug)]
+#[argh(
+    subcommand,
+    name = "insert",
+    description = "Insert secret from file or stdin"
+)]
+struct InsertCommand {
+    #[argh(positional)]
+    name: String,
+
+    #[argh(option, description = "file to be inserted as secret")]
+    file: Option<PathBuf>,
+}
+
 #[derive(FromArgs, Debug)]
 #[argh(subcommand, name = "list", description = "List secrets")]
 struct ListCommand {
@@ -118,6 +282,55 @@ struct ListCommand {
     pattern: Option<String>,
 }
 
+#[derive(FromArgs, Debug)]
+#[argh(
+    subcommand,
+    name = "otp",
+    description = "Generate one-time password from otpauth uri"
+)]
+struct OtpCommand {
+    #[argh(positional)]
+    name: Option<String>,
+
+    #[argh(switch, description = "copy one-time password to clipboard")]
+    clip: bool,
+
+    #[argh(subcommand)]
+    subcmd: Option<OtpSubcommand>,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand)]
+enum OtpSubcommand {
+    Import(OtpImportCommand),
+    Insert(OtpInsertCommand),
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(
+    subcommand,
+    name = "import",
+    description = "Add otpauth uri from qr code image to existing secret"
+)]
+struct OtpImportCommand {
+    #[argh(positional)]
+    name: String,
+
+    #[argh(option, description = "png or jpeg image file of qr code")]
+    qr: PathBuf,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(
+    subcommand,
+    name = "insert",
+    description = "Add otpauth uri from stdin to existing secret"
+)]
+struct OtpInsertCommand {
+    #[argh(positional)]
+    name: String,
+}
+
 #[derive(FromArgs, Debug)]
 #[argh(subcommand, name = "pwgen", description = "Generate passwords")]
 struct PwgenCommand {
@@ -132,6 +345,60 @@ struct PwgenCommand {
 
     #[argh(switch, description = "include symbol characters in password")]
     symbol: bool,
+
+    #[argh(switch, description = "print entropy bits of generated passwords")]
+    show_entropy: bool,
+
+    #[argh(switch, description = "generate numeric pin avoiding trivial sequences")]
+    pin: bool,
+
+    #[argh(switch, description = "generate pronounceable password of syllables")]
+    pronounceable: bool,
+
+    #[argh(
+        switch,
+        description = "generate usernames of random characters, or of words with --words"
+    )]
+    username: bool,
+
+    #[argh(
+        switch,
+        description = "generate email aliases from email_alias address of config"
+    )]
+    email_alias: bool,
+
+    #[argh(
+        option,
+        description = "site requirements in passwordrules syntax, e.g. \"required: upper; minlength: 12\""
+    )]
+    rules: Option<String>,
+
+    #[argh(
+        option,
+        description = "characters to use in password, e.g. [:alnum:] or a-z0-9"
+    )]
+    charset: Option<String>,
+
+    #[argh(option, description = "characters to exclude from password")]
+    exclude: Option<String>,
+
+    #[argh(switch, description = "exclude ambiguous characters like l1O0")]
+    no_ambiguous: bool,
+
+    #[argh(option, description = "number of words to generate passphrase")]
+    words: Option<usize>,
+
+    #[argh(
+        option,
+        description = "separator between words, default: - for passphrase, _ for username"
+    )]
+    separator: Option<String>,
+
+    #[argh(switch, description = "capitalize each word of passphrase")]
+    capitalize: bool,
+
+    #[argh(switch, description = "insert a digit into passphrase")]
+    digit: bool,
 }
 
 #[derive(FromArgs, Debug)]
@@ -143,6 +410,42 @@ struct PwgenCommand {
 struct ShowCommand {
     #[argh(positional)]
     name: String,
+
+    #[argh(switch, description = "output whole secret contents as is")]
+    raw: bool,
+
+    #[argh(
+        option,
+        short = 'o',
+        description = "file to write raw secret contents to"
+    )]
+    output: Option<PathBuf>,
+
+    #[argh(switch, description = "display password or field as qr code")]
+    qrcode: bool,
+
+    #[argh(option, description = "field to display as qr code instead of password")]
+    field: Option<String>,
+
+    #[argh(option, description = "png or svg file to write qr code to")]
+    qrcode_out: Option<PathBuf>,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(
+    subcommand,
+    name = "type",
+    description = "Type secret fields by autotype sequence"
+)]
+struct TypeCommand {
+    #[argh(positional)]
+    name: String,
+
+    #[argh(
+        option,
+        description = "typer command, wtype, xdotool, ydotool or custom command"
+    )]
+    typer: Option<String>,
 }
 
 #[derive(FromArgs, Debug)]
@@ -152,15 +455,17 @@ struct VersionCommand {}
 fn main() {
     if let Err(err) = aikot_main() {
         eprintln!("{}", err);
+        exit(1);
     }
 }
 
 fn aikot_main() -> Result<(), Error> {
     let aikot_env = AikotEnv::from_env()?;
-    #[cfg(windows)]
+    #[cfg(any(windows, target_os = "macos"))]
     if let Some(arg) = args().nth(1) {
         if arg == "unclip" {
-            return cmd::cmd_unclip(&aikot_env);
+            let timeout = args().nth(2).and_then(|s| s.parse().ok());
+            return cmd::cmd_unclip(&aikot_env, timeout);
         }
     }
     let cmd: AikotCommand = argh::from_env();
@@ -170,36 +475,197 @@ fn aikot_main() -> Result<(), Error> {
             length,
             no_generate,
             symbol,
+            entry_type,
+            rules,
+            charset,
+            exclude,
+            no_ambiguous,
+            words,
+            separator,
+            capitalize,
+            digit,
         }) => {
-            let opwgen = if no_generate {
+            let pwgen_opts = if no_generate {
                 None
             } else {
-                Some(PwGen::new(length, symbol)?)
+                let opts = PwGenOptions {
+                    length,
+                    symbol,
+                    rules,
+                    charset,
+                    exclude,
+                    no_ambiguous,
+                    words,
+                    separator,
+                    capitalize,
+                    digit,
+                    ..Default::default()
+                };
+                Some(opts)
             };
-            cmd::cmd_add(&aikot_env, &name, opwgen.as_ref())
+            cmd::cmd_add(&aikot_env, &name, pwgen_opts.as_ref(), entry_type)
+        }
+        AikotSubcommand::Audit(AuditCommand {
+            min_length,
+            min_score,
+            max_findings,
+            hibp_file,
+        }) => {
+            let opts = AuditOptions {
+                min_length,
+                min_score,
+            };
+            cmd::cmd_audit(&aikot_env, &opts, max_findings, hibp_file.as_deref())
+        }
+        AikotSubcommand::Browse(BrowseCommand {
+            name,
+            index,
+            print,
+            clip,
+        }) => {
+            let clip_opts = if clip {
+                Some(cmd::clip_options(&aikot_env, ClipBackend::Auto, None, None)?)
+            } else {
+                None
+            };
+            cmd::cmd_browse(&aikot_env, &name, index, print, clip_opts.as_ref())
+        }
+        AikotSubcommand::CheckStrength(CheckStrengthCommand { name }) => {
+            cmd::cmd_check_strength(&aikot_env, &name)
+        }
+        AikotSubcommand::Clip(ClipCommand {
+            name,
+            timeout,
+            backend,
+            selection,
+            sequence,
+        }) => {
+            let opts = cmd::clip_options(&aikot_env, backend, selection, timeout)?;
+            if let Some(sequence) = sequence {
+                let fields = sequence.split(',').map(|s| s.trim()).collect::<Vec<_>>();
+                cmd::cmd_clip_sequence(&aikot_env, &name, &fields, &opts)
+            } else {
+                cmd::cmd_clip(&aikot_env, &name, &opts)
+            }
         }
-        AikotSubcommand::Browse(BrowseCommand { name }) => cmd::cmd_browse(&aikot_env, &name),
-        AikotSubcommand::Clip(ClipCommand { name }) => cmd::cmd_clip(&aikot_env, &name),
         AikotSubcommand::Completion(CompletionCommand { shell }) => {
             cmd::cmd_completion(&aikot_env, shell)
         }
         AikotSubcommand::Edit(EditCommand { name }) => cmd::cmd_edit(&aikot_env, &name),
         AikotSubcommand::Init(InitCommand { gpg_ids }) => cmd::cmd_init(&aikot_env, &gpg_ids),
+        AikotSubcommand::Insert(InsertCommand { name, file }) => {
+            cmd::cmd_insert(&aikot_env, &name, file.as_deref())
+        }
         AikotSubcommand::List(ListCommand { pattern }) => {
             cmd::cmd_list(&aikot_env, pattern.as_deref())
         }
+        AikotSubcommand::Otp(OtpCommand { name, clip, subcmd }) => match (subcmd, name) {
+            (Some(OtpSubcommand::Import(OtpImportCommand { name, qr })), _) => {
+                cmd::cmd_otp_import(&aikot_env, &name, &qr)
+            }
+            (Some(OtpSubcommand::Insert(OtpInsertCommand { name })), _) => {
+                cmd::cmd_otp_insert(&aikot_env, &name)
+            }
+            (None, Some(name)) => {
+                let clip_opts = if clip {
+                    Some(cmd::clip_options(&aikot_env, ClipBackend::Auto, None, None)?)
+                } else {
+                    None
+                };
+                cmd::cmd_otp(&aikot_env, &name, clip_opts.as_ref())
+            }
+            (None, None) => Err(anyhow!("secret name is required")),
+        },
         AikotSubcommand::Pwgen(PwgenCommand {
             length,
             count,
             symbol,
+            show_entropy,
+            pin,
+            pronounceable,
+            username,
+            email_alias,
+            rules,
+            charset,
+            exclude,
+            no_ambiguous,
+            words,
+            separator,
+            capitalize,
+            digit,
         }) => {
-            let pwgen = PwGen::new(length, symbol)?;
-            cmd::cmd_pwgen(&aikot_env, &pwgen, count)
+            let mut opts = PwGenOptions {
+                length,
+                symbol,
+                pin,
+                pronounceable,
+                rules,
+                charset,
+                exclude,
+                no_ambiguous,
+                words,
+                separator,
+                capitalize,
+                digit,
+            };
+            if show_entropy && (username || email_alias) {
+                return Err(anyhow!(
+                    "--show-entropy cannot be used with --username or --email-alias"
+                ));
+            }
+            match (username, email_alias) {
+                (true, true) => Err(anyhow!("--username and --email-alias are exclusive")),
+                (false, true) => {
+                    opts.check_email_alias()?;
+                    cmd::cmd_pwgen_email_alias(&aikot_env, count)
+                }
+                (true, false) => {
+                    opts.check_username()?;
+                    let username = match opts.words {
+                        Some(count) => Username::Words(
+                            count,
+                            opts.separator.unwrap_or_else(|| "_".to_string()),
+                        ),
+                        None => Username::Random(length),
+                    };
+                    cmd::cmd_pwgen_username(&aikot_env, &username, count)
+                }
+                (false, false) => {
+                    if opts.uses_configured_charset() {
+                        opts.charset = aikot_env.character_set(opts.symbol)?;
+                    }
+                    let pwgen = PwGen::from_options(&opts)?;
+                    // Passphrases report entropy by default.
+                    let show_entropy = show_entropy || opts.words.is_some();
+                    cmd::cmd_pwgen(&aikot_env, &pwgen, count, show_entropy)
+                }
+            }
+        }
+        AikotSubcommand::Show(ShowCommand {
+            name,
+            raw,
+            output,
+            qrcode,
+            field,
+            qrcode_out,
+        }) => {
+            if field.is_some() && !qrcode && qrcode_out.is_none() {
+                Err(anyhow!("--field requires --qrcode or --qrcode-out"))
+            } else if qrcode || qrcode_out.is_some() {
+                cmd::cmd_show_qrcode(&aikot_env, &name, field.as_deref(), qrcode_out.as_deref())
+            } else if raw || output.is_some() {
+                cmd::cmd_show_raw(&aikot_env, &name, output.as_deref())
+            } else {
+                cmd::cmd_show(&aikot_env, &name)
+            }
+        }
+        AikotSubcommand::Type(TypeCommand { name, typer }) => {
+            cmd::cmd_type(&aikot_env, &name, typer.as_deref())
         }
-        AikotSubcommand::Show(ShowCommand { name }) => cmd::cmd_show(&aikot_env, &name),
         AikotSubcommand::Version(_) => {
             println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
             Ok(())
         }
     }
 }
+
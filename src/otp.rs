@@ -0,0 +1,305 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Error;
+use data_encoding::BASE32_NOPAD;
+use hmac::digest::core_api::BlockSizeUser;
+use hmac::digest::Digest;
+use hmac::{Mac, SimpleHmac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+use crate::err::AikotError;
+
+const OTPAUTH_SCHEME: &str = "otpauth://";
+const DEFAULT_DIGITS: u32 = 6;
+const DEFAULT_PERIOD: u64 = 30;
+
+#[derive(Debug, PartialEq)]
+pub enum Algorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum OtpKind {
+    Hotp { counter: u64 },
+    Totp { period: u64 },
+}
+
+#[derive(Debug)]
+pub struct Otp {
+    kind: OtpKind,
+    secret: Vec<u8>,
+    algorithm: Algorithm,
+    digits: u32,
+}
+
+impl Otp {
+    // Parse the Key Uri Format, e.g. "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP".
+    pub fn parse(uri: &str) -> Result<Self, Error> {
+        let rest = uri
+            .trim()
+            .strip_prefix(OTPAUTH_SCHEME)
+            .ok_or_else(|| invalid("scheme must be otpauth"))?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let otp_type = path.split('/').next().unwrap_or_default();
+
+        let mut secret = None;
+        let mut algorithm = Algorithm::Sha1;
+        let mut digits = DEFAULT_DIGITS;
+        let mut period = DEFAULT_PERIOD;
+        let mut counter = None;
+        for (key, val) in query.split('&').filter_map(|p| p.split_once('=')) {
+            match key.to_ascii_lowercase().as_str() {
+                "secret" => secret = Some(decode_secret(val)?),
+                "algorithm" => {
+                    algorithm = match val.to_ascii_uppercase().as_str() {
+                        "SHA1" => Algorithm::Sha1,
+                        "SHA256" => Algorithm::Sha256,
+                        "SHA512" => Algorithm::Sha512,
+                        _ => return Err(invalid(&format!("unknown algorithm {}", val))),
+                    }
+                }
+                "digits" => {
+                    digits = val
+                        .parse()
+                        .ok()
+                        .filter(|d| (6..=8).contains(d))
+                        .ok_or_else(|| invalid("digits must be 6 to 8"))?
+                }
+                "period" => {
+                    period = val
+                        .parse()
+                        .ok()
+                        .filter(|p| *p > 0)
+                        .ok_or_else(|| invalid("period must be positive"))?
+                }
+                "counter" => {
+                    counter = Some(val.parse().map_err(|_| invalid("counter must be number"))?)
+                }
+                _ => {}
+            }
+        }
+
+        let kind = match otp_type.to_ascii_lowercase().as_str() {
+            "totp" => OtpKind::Totp { period },
+            "hotp" => OtpKind::Hotp {
+                counter: counter.ok_or_else(|| invalid("counter is required for hotp"))?,
+            },
+            _ => return Err(invalid("type must be totp or hotp")),
+        };
+        let secret = secret.ok_or_else(|| invalid("secret is required"))?;
+        Ok(Otp {
+            kind,
+            secret,
+            algorithm,
+            digits,
+        })
+    }
+
+    pub fn kind(&self) -> &OtpKind {
+        &self.kind
+    }
+
+    // Code for the current time for TOTP, or the stored counter for HOTP.
+    pub fn code(&self) -> Result<String, Error> {
+        match self.kind {
+            OtpKind::Hotp { counter } => Ok(self.code_at(counter)),
+            OtpKind::Totp { period } => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                Ok(self.code_at(now / period))
+            }
+        }
+    }
+
+    // Dynamic truncation of RFC 4226.
+    fn code_at(&self, counter: u64) -> String {
+        let msg = counter.to_be_bytes();
+        let hash = match self.algorithm {
+            Algorithm::Sha1 => hmac::<Sha1>(&self.secret, &msg),
+            Algorithm::Sha256 => hmac::<Sha256>(&self.secret, &msg),
+            Algorithm::Sha512 => hmac::<Sha512>(&self.secret, &msg),
+        };
+        let offset = (hash[hash.len() - 1] & 0xf) as usize;
+        let bin = u32::from_be_bytes([
+            hash[offset] & 0x7f,
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]);
+        format!(
+            "{:0width$}",
+            bin % 10u32.pow(self.digits),
+            width = self.digits as usize
+        )
+    }
+}
+
+// Find the otpauth URI in the secret, which may be prefixed by a key like "otp: ".
+pub fn find_uri(contents: &str) -> Option<&str> {
+    contents
+        .lines()
+        .find_map(|line| line.find(OTPAUTH_SCHEME).map(|i| line[i..].trim()))
+}
+
+// Append the otpauth URI to the end of the secret, None if it already has one.
+pub fn append_uri(contents: &str, uri: &str) -> Option<String> {
+    if find_uri(contents).is_some() {
+        None
+    } else if contents.is_empty() || contents.ends_with('\n') {
+        Some(format!("{}{}\n", contents, uri))
+    } else {
+        Some(format!("{}\n{}\n", contents, uri))
+    }
+}
+
+// Replace or append the counter parameter, keeping the rest of the URI as is.
+pub fn with_counter(uri: &str, counter: u64) -> String {
+    let (base, query) = uri.split_once('?').unwrap_or((uri, ""));
+    let mut params = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            if p.to_ascii_lowercase().starts_with("counter=") {
+                format!("counter={}", counter)
+            } else {
+                p.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+    if !params.iter().any(|p| p.starts_with("counter=")) {
+        params.push(format!("counter={}", counter));
+    }
+    format!("{}?{}", base, params.join("&"))
+}
+
+fn hmac<D>(key: &[u8], msg: &[u8]) -> Vec<u8>
+where
+    D: Digest + BlockSizeUser,
+{
+    let mut mac = SimpleHmac::<D>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(msg);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn decode_secret(val: &str) -> Result<Vec<u8>, Error> {
+    let normalized = val
+        .chars()
+        .filter(|c| *c != '=' && *c != ' ' && *c != '-')
+        .collect::<String>()
+        .to_ascii_uppercase();
+    BASE32_NOPAD
+        .decode(normalized.as_bytes())
+        .ok()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| invalid("secret must be base32"))
+}
+
+fn invalid(reason: &str) -> Error {
+    AikotError::InvalidOtpUri {
+        reason: reason.to_string(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn otp(secret: &[u8], algorithm: Algorithm, digits: u32) -> Otp {
+        Otp {
+            kind: OtpKind::Totp { period: 30 },
+            secret: secret.to_vec(),
+            algorithm,
+            digits,
+        }
+    }
+
+    #[test]
+    fn hotp_rfc4226_vectors() {
+        let otp = otp(b"12345678901234567890", Algorithm::Sha1, 6);
+        let codes = (0..10).map(|c| otp.code_at(c)).collect::<Vec<_>>();
+        assert_eq!(
+            codes,
+            vec![
+                "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+                "399871", "520489"
+            ]
+        );
+    }
+
+    #[test]
+    fn totp_rfc6238_vectors() {
+        let seed = b"1234567890123456789012345678901234567890123456789012345678901234";
+        let sha1 = otp(&seed[..20], Algorithm::Sha1, 8);
+        let sha256 = otp(&seed[..32], Algorithm::Sha256, 8);
+        let sha512 = otp(seed, Algorithm::Sha512, 8);
+        assert_eq!(sha1.code_at(59 / 30), "94287082");
+        assert_eq!(sha256.code_at(59 / 30), "46119246");
+        assert_eq!(sha512.code_at(59 / 30), "90693936");
+        assert_eq!(sha1.code_at(1111111109 / 30), "07081804");
+        assert_eq!(sha256.code_at(1111111109 / 30), "68084774");
+        assert_eq!(sha512.code_at(1111111109 / 30), "25091201");
+    }
+
+    #[test]
+    fn parse_uri() {
+        let otp = Otp::parse(
+            "otpauth://totp/Example:alice@example.com?secret=jbswy3dpehpk3pxp&issuer=Example&algorithm=SHA256&digits=8&period=60",
+        )
+        .unwrap();
+        assert_eq!(otp.kind(), &OtpKind::Totp { period: 60 });
+        assert_eq!(otp.secret, b"Hello!\xde\xad\xbe\xef");
+        assert_eq!(otp.algorithm, Algorithm::Sha256);
+        assert_eq!(otp.digits, 8);
+
+        let otp = Otp::parse("otpauth://hotp/foo?secret=JBSWY3DPEHPK3PXP&counter=3").unwrap();
+        assert_eq!(otp.kind(), &OtpKind::Hotp { counter: 3 });
+        assert_eq!(otp.algorithm, Algorithm::Sha1);
+        assert_eq!(otp.digits, 6);
+    }
+
+    #[test]
+    fn parse_invalid_uri() {
+        assert!(Otp::parse("https://example.com/?secret=JBSWY3DPEHPK3PXP").is_err());
+        assert!(Otp::parse("otpauth://totp/foo").is_err());
+        assert!(Otp::parse("otpauth://totp/foo?secret=!!").is_err());
+        assert!(Otp::parse("otpauth://hotp/foo?secret=JBSWY3DPEHPK3PXP").is_err());
+        assert!(Otp::parse("otpauth://totp/foo?secret=JBSWY3DPEHPK3PXP&digits=4").is_err());
+        assert!(Otp::parse("otpauth://totp/foo?secret=JBSWY3DPEHPK3PXP&algorithm=MD5").is_err());
+    }
+
+    #[test]
+    fn find_and_update_counter() {
+        let contents = "pw\nuser: me\notp: otpauth://hotp/foo?secret=AAAA&counter=9&digits=6\n";
+        let uri = find_uri(contents).unwrap();
+        assert_eq!(uri, "otpauth://hotp/foo?secret=AAAA&counter=9&digits=6");
+        assert_eq!(
+            with_counter(uri, 10),
+            "otpauth://hotp/foo?secret=AAAA&counter=10&digits=6"
+        );
+        assert_eq!(
+            with_counter("otpauth://hotp/foo?secret=AAAA", 1),
+            "otpauth://hotp/foo?secret=AAAA&counter=1"
+        );
+        assert_eq!(find_uri("pw\nuser: me\n"), None);
+    }
+
+    #[test]
+    fn append_uri_once() {
+        let uri = "otpauth://totp/foo?secret=AAAA";
+        assert_eq!(
+            append_uri("pw\nuser: me", uri).unwrap(),
+            "pw\nuser: me\notpauth://totp/foo?secret=AAAA\n"
+        );
+        assert_eq!(
+            append_uri("pw\n", uri).unwrap(),
+            "pw\notpauth://totp/foo?secret=AAAA\n"
+        );
+        assert_eq!(
+            append_uri("pw\notp: otpauth://totp/bar?secret=BBBB\n", uri),
+            None
+        );
+    }
+}
@@ -1,19 +1,158 @@
+use std::cmp::min;
 use std::fmt;
 use std::iter;
+use std::sync::OnceLock;
 
 use rand::distributions::{Alphanumeric, Distribution};
+use rand::seq::SliceRandom;
 use rand::{thread_rng, Rng};
 
 use crate::err::AikotError;
+use crate::rules::PasswordRules;
 
 const SYMBOL_CHARS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
 
+// Characters easily confused when read aloud or in some fonts.
+const AMBIGUOUS_CHARS: &str = "01IOlo|";
+const ALNUM_SET: &str = "[:alnum:]";
+const ALNUM_SYMBOL_SET: &str = "[:alnum:][:punct:]";
+
+// Koremutake syllables, 7 bits each, https://shorl.com/koremutake.php.
+const KOREMUTAKE_SYLLABLES: [&str; 128] = [
+    "ba", "be", "bi", "bo", "bu", "by", "da", "de", "di", "do", "du", "dy", "fe", "fi", "fo", "fu",
+    "fy", "ga", "ge", "gi", "go", "gu", "gy", "ha", "he", "hi", "ho", "hu", "hy", "ja", "je", "ji",
+    "jo", "ju", "jy", "ka", "ke", "ki", "ko", "ku", "ky", "la", "le", "li", "lo", "lu", "ly", "ma",
+    "me", "mi", "mo", "mu", "my", "na", "ne", "ni", "no", "nu", "ny", "pa", "pe", "pi", "po", "pu",
+    "py", "ra", "re", "ri", "ro", "ru", "ry", "sa", "se", "si", "so", "su", "sy", "ta", "te", "ti",
+    "to", "tu", "ty", "va", "ve", "vi", "vo", "vu", "vy", "bra", "bre", "bri", "bro", "bru", "bry",
+    "dra", "dre", "dri", "dro", "dru", "dry", "fra", "fre", "fri", "fro", "fru", "fry", "gra",
+    "gre", "gri", "gro", "gru", "gry", "pra", "pre", "pri", "pro", "pru", "pry", "sta", "ste",
+    "sti", "sto", "stu", "sty", "tra", "tre", "tri",
+];
+
+// EFF long wordlist for five dice, https://www.eff.org/dice (CC BY 3.0 US).
+const EFF_LARGE_WORDLIST: &str = include_str!("eff_large_wordlist.txt");
+
 pub struct Alphanum;
 pub struct AlphanumSymbol;
 
+pub struct Pin;
+pub struct Pronounceable;
+
+pub struct Charset {
+    chars: Vec<char>,
+}
+
+pub struct Diceware {
+    pub separator: String,
+    pub capitalize: bool,
+    pub digit: bool,
+}
+
 pub enum PwGen {
     An(Alphanum, usize),
     Ans(AlphanumSymbol, usize),
+    Custom(Charset, usize),
+    Pin(Pin, usize),
+    Pronounceable(Pronounceable, usize),
+    Rules(PasswordRules, usize),
+    Words(Diceware, usize),
+}
+
+#[derive(Clone, Default)]
+pub struct PwGenOptions {
+    pub length: usize,
+    pub symbol: bool,
+    pub pin: bool,
+    pub pronounceable: bool,
+    pub rules: Option<String>,
+    pub charset: Option<String>,
+    pub exclude: Option<String>,
+    pub no_ambiguous: bool,
+    pub words: Option<usize>,
+    pub separator: Option<String>,
+    pub capitalize: bool,
+    pub digit: bool,
+}
+
+impl PwGenOptions {
+    // Rules of a template apply unless the generator or character set is given explicitly.
+    pub fn default_rules(&mut self, rules: Option<&str>) {
+        if self.generator_options() || self.charset_options() {
+            return;
+        }
+        self.rules = rules.map(|rules| rules.to_string());
+    }
+
+    // The configured character set only applies to the character based generators.
+    pub fn uses_configured_charset(&self) -> bool {
+        self.charset.is_none() && !self.generator_options()
+    }
+
+    // Usernames only take the length, or words with a separator.
+    pub fn check_username(&self) -> Result<(), AikotError> {
+        if self.password_options() {
+            return Err(conflict("password options cannot be used with --username"));
+        }
+        Ok(())
+    }
+
+    pub fn check_email_alias(&self) -> Result<(), AikotError> {
+        if self.password_options() || self.words.is_some() || self.separator.is_some() {
+            return Err(conflict(
+                "generator options cannot be used with --email-alias",
+            ));
+        }
+        Ok(())
+    }
+
+    // Generators are exclusive, and each takes only its own options.
+    fn check(&self) -> Result<(), AikotError> {
+        let modes = [
+            ("--words", self.words.is_some()),
+            ("--pin", self.pin),
+            ("--pronounceable", self.pronounceable),
+            ("--rules", self.rules.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, given)| *given)
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+        if modes.len() > 1 {
+            return Err(conflict(&format!("{} are exclusive", modes.join(" and "))));
+        }
+        if let Some(mode) = modes.first() {
+            if self.charset_options() {
+                return Err(conflict(&format!(
+                    "character set options cannot be used with {}",
+                    mode
+                )));
+            }
+        }
+        if self.words.is_none() && (self.separator.is_some() || self.capitalize || self.digit) {
+            return Err(conflict(
+                "--separator, --capitalize and --digit require --words",
+            ));
+        }
+        Ok(())
+    }
+
+    fn password_options(&self) -> bool {
+        self.pin
+            || self.pronounceable
+            || self.rules.is_some()
+            || self.charset_options()
+            || self.capitalize
+            || self.digit
+    }
+
+    fn generator_options(&self) -> bool {
+        self.rules.is_some() || self.words.is_some() || self.pin || self.pronounceable
+    }
+
+    fn charset_options(&self) -> bool {
+        self.symbol || self.charset.is_some() || self.exclude.is_some() || self.no_ambiguous
+    }
 }
 
 impl PwGen {
@@ -23,22 +162,73 @@ impl PwGen {
         } else {
             PwGen::An(Alphanum, length)
         };
+        pwgen.check_minimum_length(length)
+    }
 
-        let min_len = pwgen.minimum_length();
-        if length < min_len {
-            Err(AikotError::MinimumLength {
-                pwgen: format!("{}", pwgen),
-                min_len,
-            })
+    pub fn words(count: usize, diceware: Diceware) -> Result<Self, AikotError> {
+        PwGen::Words(diceware, count).check_minimum_length(count)
+    }
+
+    pub fn custom(length: usize, charset: Charset) -> Result<Self, AikotError> {
+        PwGen::Custom(charset, length).check_minimum_length(length)
+    }
+
+    // Character set options switch to custom generator based on the default classes.
+    pub fn from_options(opts: &PwGenOptions) -> Result<Self, AikotError> {
+        opts.check()?;
+        if let Some(count) = opts.words {
+            let diceware = Diceware {
+                separator: opts.separator.clone().unwrap_or_else(|| "-".to_string()),
+                capitalize: opts.capitalize,
+                digit: opts.digit,
+            };
+            return PwGen::words(count, diceware);
+        }
+        if opts.pin {
+            return PwGen::Pin(Pin, opts.length).check_minimum_length(opts.length);
+        }
+        if opts.pronounceable {
+            return PwGen::Pronounceable(Pronounceable, opts.length)
+                .check_minimum_length(opts.length);
+        }
+        if let Some(rules) = &opts.rules {
+            let rules = rules.parse::<PasswordRules>()?;
+            let length = rules.length(opts.length);
+            return PwGen::Rules(rules, length).check_minimum_length(length);
+        }
+        if opts.charset.is_none() && opts.exclude.is_none() && !opts.no_ambiguous {
+            return PwGen::new(opts.length, opts.symbol);
+        }
+        let default_set = if opts.symbol {
+            ALNUM_SYMBOL_SET
         } else {
-            Ok(pwgen)
+            ALNUM_SET
+        };
+        let spec = opts.charset.as_deref().unwrap_or(default_set);
+        let mut charset = Charset::parse(spec)?;
+        if let Some(exclude) = &opts.exclude {
+            charset.exclude(exclude);
+        }
+        if opts.no_ambiguous {
+            charset.exclude(AMBIGUOUS_CHARS);
         }
+        if charset.chars.is_empty() {
+            return Err(AikotError::InvalidCharset {
+                spec: spec.to_string(),
+            });
+        }
+        PwGen::custom(opts.length, charset)
     }
 
     pub fn try_generate(&self) -> Result<String, AikotError> {
         let opass = match self {
             PwGen::An(x, len) => x.try_generate(*len),
             PwGen::Ans(x, len) => x.try_generate(*len),
+            PwGen::Custom(x, len) => x.try_generate(*len),
+            PwGen::Pin(x, len) => x.try_generate(*len),
+            PwGen::Pronounceable(x, len) => x.try_generate(*len),
+            PwGen::Rules(x, len) => x.try_generate(*len),
+            PwGen::Words(x, count) => Some(x.generate(*count)),
         };
 
         if let Some(pass) = opass {
@@ -50,10 +240,40 @@ impl PwGen {
         }
     }
 
+    // Bits of entropy for the generator, a lower bound for character based ones.
+    pub fn entropy(&self) -> f64 {
+        match self {
+            PwGen::An(x, len) => x.entropy(*len),
+            PwGen::Ans(x, len) => x.entropy(*len),
+            PwGen::Custom(x, len) => x.entropy(*len),
+            PwGen::Pin(x, len) => x.entropy(*len),
+            PwGen::Pronounceable(x, len) => x.entropy(*len),
+            PwGen::Rules(x, len) => x.entropy(*len),
+            PwGen::Words(x, count) => x.entropy(*count),
+        }
+    }
+
+    fn check_minimum_length(self, length: usize) -> Result<Self, AikotError> {
+        let min_len = self.minimum_length();
+        if length < min_len {
+            Err(AikotError::MinimumLength {
+                pwgen: format!("{}", self),
+                min_len,
+            })
+        } else {
+            Ok(self)
+        }
+    }
+
     fn minimum_length(&self) -> usize {
         match self {
             PwGen::An(x, _) => x.minimum_length(),
             PwGen::Ans(x, _) => x.minimum_length(),
+            PwGen::Custom(x, _) => x.minimum_length(),
+            PwGen::Pin(x, _) => x.minimum_length(),
+            PwGen::Pronounceable(x, _) => x.minimum_length(),
+            PwGen::Rules(x, _) => x.minimum_length(),
+            PwGen::Words(x, _) => x.minimum_length(),
         }
     }
 }
@@ -63,33 +283,42 @@ impl fmt::Display for PwGen {
         match self {
             PwGen::An(_, len) => write!(f, "length: {}, class: alphanum", len),
             PwGen::Ans(_, len) => write!(f, "length: {}, class: alphanum+symbol", len),
+            PwGen::Custom(_, len) => write!(f, "length: {}, class: custom", len),
+            PwGen::Pin(_, len) => write!(f, "length: {}, class: pin", len),
+            PwGen::Pronounceable(_, len) => write!(f, "length: {}, class: pronounceable", len),
+            PwGen::Rules(_, len) => write!(f, "length: {}, class: rules", len),
+            PwGen::Words(_, count) => write!(f, "words: {}, class: diceware", count),
         }
     }
 }
 
-pub trait PasswordClass: Distribution<char> {
+pub trait PasswordClass {
+    // The generated password is verified again, so that it is never returned when invalid.
     fn try_generate(&self, len: usize) -> Option<String>
     where
         Self: Sized,
     {
-        let mut rng = thread_rng();
-        for _i in 1..100 {
-            let pass: String = iter::repeat(())
-                .map(|()| rng.sample(self))
-                .take(len)
-                .collect();
-            if self.verify(&pass) {
-                return Some(pass);
-            }
-        }
-        None
+        self.generate_with(len, &mut thread_rng())
+            .filter(|pass| self.verify(pass))
     }
 
+    fn generate_with<R>(&self, len: usize, rng: &mut R) -> Option<String>
+    where
+        Self: Sized,
+        R: Rng;
+
+    fn entropy(&self, len: usize) -> f64;
+
     fn minimum_length(&self) -> usize;
 
     fn verify(&self, pass: &str) -> bool;
 }
 
+// Classes of independently sampled characters, generated by construct.
+pub trait CharClass: Distribution<char> {
+    fn required_classes(&self) -> Vec<Vec<char>>;
+}
+
 impl Distribution<char> for Alphanum {
     fn sample<R>(&self, rng: &mut R) -> char
     where
@@ -99,7 +328,28 @@ impl Distribution<char> for Alphanum {
     }
 }
 
+impl CharClass for Alphanum {
+    fn required_classes(&self) -> Vec<Vec<char>> {
+        vec![
+            ('a'..='z').collect(),
+            ('A'..='Z').collect(),
+            ('0'..='9').collect(),
+        ]
+    }
+}
+
 impl PasswordClass for Alphanum {
+    fn generate_with<R>(&self, len: usize, rng: &mut R) -> Option<String>
+    where
+        R: Rng,
+    {
+        construct(self, len, rng)
+    }
+
+    fn entropy(&self, len: usize) -> f64 {
+        class_entropy(&self.required_classes(), 62, len)
+    }
+
     fn minimum_length(&self) -> usize {
         3
     }
@@ -135,7 +385,29 @@ impl Distribution<char> for AlphanumSymbol {
     }
 }
 
+impl CharClass for AlphanumSymbol {
+    fn required_classes(&self) -> Vec<Vec<char>> {
+        vec![
+            ('a'..='z').collect(),
+            ('A'..='Z').collect(),
+            ('0'..='9').collect(),
+            SYMBOL_CHARS.chars().collect(),
+        ]
+    }
+}
+
 impl PasswordClass for AlphanumSymbol {
+    fn generate_with<R>(&self, len: usize, rng: &mut R) -> Option<String>
+    where
+        R: Rng,
+    {
+        construct(self, len, rng)
+    }
+
+    fn entropy(&self, len: usize) -> f64 {
+        class_entropy(&self.required_classes(), 94, len)
+    }
+
     fn minimum_length(&self) -> usize {
         4
     }
@@ -151,6 +423,284 @@ impl PasswordClass for AlphanumSymbol {
     }
 }
 
+impl Charset {
+    // Parse a set like tr(1), e.g. "[:alnum:]", "a-f0-9" or "abc!?".
+    pub fn parse(spec: &str) -> Result<Self, AikotError> {
+        let invalid = || AikotError::InvalidCharset {
+            spec: spec.to_string(),
+        };
+        let mut chars = Vec::new();
+        let mut rest = spec;
+        while let Some(first) = rest.chars().next() {
+            if let Some(after) = rest.strip_prefix("[:") {
+                let end = after.find(":]").ok_or_else(invalid)?;
+                chars.extend(class_chars(&after[..end]).ok_or_else(invalid)?);
+                rest = &after[end + 2..];
+                continue;
+            }
+            let tail = &rest[first.len_utf8()..];
+            let mut range = tail.chars();
+            if let (Some('-'), Some(last)) = (range.next(), range.next()) {
+                if first <= last {
+                    chars.extend(first..=last);
+                    rest = range.as_str();
+                    continue;
+                }
+            }
+            chars.push(first);
+            rest = tail;
+        }
+        chars.sort_unstable();
+        chars.dedup();
+        if chars.is_empty() {
+            Err(invalid())
+        } else {
+            Ok(Charset { chars })
+        }
+    }
+
+    pub fn exclude(&mut self, excluded: &str) {
+        self.chars.retain(|c| !excluded.contains(*c));
+    }
+
+    // Only the classes present in the set are required in passwords.
+    fn class_predicates(&self) -> Vec<fn(char) -> bool> {
+        let classes: [fn(char) -> bool; 4] = [lower_char, upper_char, number_char, symbol_char];
+        classes
+            .into_iter()
+            .filter(|pred| self.chars.iter().any(|c| pred(*c)))
+            .collect()
+    }
+}
+
+impl Distribution<char> for Charset {
+    fn sample<R>(&self, rng: &mut R) -> char
+    where
+        R: rand::Rng + ?Sized,
+    {
+        self.chars[rng.gen_range(0..self.chars.len())]
+    }
+}
+
+impl CharClass for Charset {
+    fn required_classes(&self) -> Vec<Vec<char>> {
+        self.class_predicates()
+            .into_iter()
+            .map(|pred| self.chars.iter().copied().filter(|c| pred(*c)).collect())
+            .collect()
+    }
+}
+
+impl PasswordClass for Charset {
+    fn generate_with<R>(&self, len: usize, rng: &mut R) -> Option<String>
+    where
+        R: Rng,
+    {
+        construct(self, len, rng)
+    }
+
+    fn entropy(&self, len: usize) -> f64 {
+        class_entropy(&self.required_classes(), self.chars.len(), len)
+    }
+
+    fn minimum_length(&self) -> usize {
+        self.class_predicates().len().max(1)
+    }
+
+    fn verify(&self, pass: &str) -> bool {
+        all_predicts(&self.class_predicates(), pass)
+    }
+}
+
+// Digits are chosen one by one, banning those which make a trivial PIN.
+impl PasswordClass for Pin {
+    fn generate_with<R>(&self, len: usize, rng: &mut R) -> Option<String>
+    where
+        R: Rng,
+    {
+        let mut pin = Vec::<u8>::with_capacity(len);
+        for i in 0..len {
+            let candidates = (0..10)
+                .filter(|d| {
+                    pin.push(*d);
+                    let trivial = trivial_tail(&pin) || (i == len - 1 && periodic(&pin));
+                    pin.pop();
+                    !trivial
+                })
+                .collect::<Vec<_>>();
+            if candidates.is_empty() {
+                return None;
+            }
+            pin.push(candidates[rng.gen_range(0..candidates.len())]);
+        }
+        Some(pin.iter().map(|d| char::from(b'0' + d)).collect())
+    }
+
+    // At most two digits are banned after the first two.
+    fn entropy(&self, len: usize) -> f64 {
+        let free = min(len, 2) as f64;
+        free * 10f64.log2() + (len as f64 - free) * 8f64.log2()
+    }
+
+    fn minimum_length(&self) -> usize {
+        4
+    }
+
+    fn verify(&self, pass: &str) -> bool {
+        let digits = pass
+            .chars()
+            .map(|c| c.to_digit(10).map(|d| d as u8))
+            .collect::<Option<Vec<_>>>();
+        match digits {
+            Some(digits) => {
+                !periodic(&digits) && (1..=digits.len()).all(|i| !trivial_tail(&digits[..i]))
+            }
+            None => false,
+        }
+    }
+}
+
+// Syllables are concatenated and the last one is cut at the length.
+impl PasswordClass for Pronounceable {
+    fn generate_with<R>(&self, len: usize, rng: &mut R) -> Option<String>
+    where
+        R: Rng,
+    {
+        let mut pass = String::with_capacity(len + 2);
+        while pass.len() < len {
+            pass.push_str(KOREMUTAKE_SYLLABLES.choose(rng).unwrap_or(&""));
+        }
+        pass.truncate(len);
+        Some(pass)
+    }
+
+    // Each syllable has at most 3 characters.
+    fn entropy(&self, len: usize) -> f64 {
+        (len / 3) as f64 * (KOREMUTAKE_SYLLABLES.len() as f64).log2()
+    }
+
+    fn minimum_length(&self) -> usize {
+        4
+    }
+
+    fn verify(&self, pass: &str) -> bool {
+        !pass.is_empty() && pass.chars().all(|c| c.is_ascii_lowercase())
+    }
+}
+
+impl Diceware {
+    fn generate(&self, count: usize) -> String {
+        let wordlist = wordlist();
+        let mut rng = thread_rng();
+        let mut words = (0..count)
+            .map(|_| wordlist.choose(&mut rng).unwrap_or(&"").to_string())
+            .collect::<Vec<_>>();
+        if self.capitalize {
+            for word in words.iter_mut() {
+                if let Some(first) = word.get_mut(..1) {
+                    first.make_ascii_uppercase();
+                }
+            }
+        }
+        if self.digit {
+            let digit = char::from(b'0' + rng.gen_range(0..10));
+            words[rng.gen_range(0..count)].push(digit);
+        }
+        words.join(&self.separator)
+    }
+
+    // Capitalization adds nothing since every word is capitalized.
+    fn entropy(&self, count: usize) -> f64 {
+        let mut bits = count as f64 * (wordlist().len() as f64).log2();
+        if self.digit {
+            bits += (10.0 * count as f64).log2();
+        }
+        bits
+    }
+
+    fn minimum_length(&self) -> usize {
+        3
+    }
+}
+
+fn class_chars(name: &str) -> Option<Vec<char>> {
+    let chars = match name {
+        "alnum" => ('0'..='9').chain('A'..='Z').chain('a'..='z').collect(),
+        "alpha" => ('A'..='Z').chain('a'..='z').collect(),
+        "digit" => ('0'..='9').collect(),
+        "lower" => ('a'..='z').collect(),
+        "upper" => ('A'..='Z').collect(),
+        "punct" => SYMBOL_CHARS.chars().collect(),
+        "graph" => ('!'..='~').collect(),
+        "xdigit" => ('0'..='9').chain('A'..='F').chain('a'..='f').collect(),
+        _ => return None,
+    };
+    Some(chars)
+}
+
+// Three same digits or three steps like "123" and "987" at the end.
+fn trivial_tail(digits: &[u8]) -> bool {
+    match digits {
+        [.., a, b, c] => {
+            let (a, b, c) = (*a as i8, *b as i8, *c as i8);
+            (a == b && b == c) || (b - a == c - b && (c - b).abs() == 1)
+        }
+        _ => false,
+    }
+}
+
+// Repetition of a shorter block like "1212" or "123123".
+fn periodic(digits: &[u8]) -> bool {
+    let len = digits.len();
+    (1..len).any(|p| len.is_multiple_of(p) && digits.chunks(p).all(|chunk| chunk == &digits[..p]))
+}
+
+// Parsed once, since passphrases, usernames and strength estimation all look it up.
+pub fn wordlist() -> &'static [&'static str] {
+    static WORDLIST: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDLIST.get_or_init(|| {
+        EFF_LARGE_WORDLIST
+            .lines()
+            .filter_map(|line| line.split('\t').nth(1))
+            .collect()
+    })
+}
+
+fn conflict(reason: &str) -> AikotError {
+    AikotError::ConflictingOptions {
+        reason: reason.to_string(),
+    }
+}
+
+// Required characters count by their class, ignoring their positions.
+pub fn class_entropy(required: &[Vec<char>], total: usize, len: usize) -> f64 {
+    let fixed = required
+        .iter()
+        .map(|chars| (chars.len() as f64).log2())
+        .sum::<f64>();
+    fixed + len.saturating_sub(required.len()) as f64 * (total as f64).log2()
+}
+
+// Place one character of each required class, fill the rest uniformly and shuffle,
+// so that generation never fails when the length is enough for the classes.
+pub fn construct<C, R>(class: &C, len: usize, rng: &mut R) -> Option<String>
+where
+    C: CharClass,
+    R: Rng,
+{
+    let required = class.required_classes();
+    if len < required.len() || required.iter().any(|chars| chars.is_empty()) {
+        return None;
+    }
+    let mut chars = required
+        .iter()
+        .map(|chars| chars[rng.gen_range(0..chars.len())])
+        .collect::<Vec<_>>();
+    chars.extend(iter::repeat_with(|| rng.sample(class)).take(len - required.len()));
+    chars.shuffle(rng);
+    Some(chars.into_iter().collect())
+}
+
 fn lower_char(c: char) -> bool {
     c.is_lowercase()
 }
@@ -187,44 +737,47 @@ where
 
 #[cfg(test)]
 mod test {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
     use super::*;
 
     #[test]
     fn test_all_predicts_for_empty_pred() {
-        let mut v: Vec<Box<dyn Fn(char) -> bool>> = vec![];
-        assert!(all_predicts(&mut v, "foo"));
+        let v: Vec<Box<dyn Fn(char) -> bool>> = vec![];
+        assert!(all_predicts(&v, "foo"));
     }
 
     #[test]
     fn test_all_predicts_for_one_pred_pass() {
-        let mut v = vec![|c| c == 'f'];
-        assert!(all_predicts(&mut v, "foo"));
+        let v = vec![|c| c == 'f'];
+        assert!(all_predicts(&v, "foo"));
     }
 
     #[test]
     fn test_all_predicts_for_one_pred_fail() {
-        let mut v = vec![|c| c == 'g'];
-        assert!(!all_predicts(&mut v, "foo"));
+        let v = vec![|c| c == 'g'];
+        assert!(!all_predicts(&v, "foo"));
     }
 
     #[test]
     fn test_all_predicts_for_one_pred_pass_of_3preds() {
-        let mut v: Vec<Box<dyn Fn(char) -> bool>> = vec![
+        let v: Vec<Box<dyn Fn(char) -> bool>> = vec![
             Box::new(|c| c == 'g'),
             Box::new(|c| c == 'h'),
             Box::new(|c| c == 'f'),
         ];
-        assert!(!all_predicts(&mut v, "foo"));
+        assert!(!all_predicts(&v, "foo"));
     }
 
     #[test]
     fn test_all_predicts_for_all_preds_pass_of_3preds() {
-        let mut v: Vec<Box<dyn Fn(char) -> bool>> = vec![
+        let v: Vec<Box<dyn Fn(char) -> bool>> = vec![
             Box::new(|c| c == 'f'),
             Box::new(|c| c == 'o'),
             Box::new(|c| c != 'g'),
         ];
-        assert!(all_predicts(&mut v, "foo"));
+        assert!(all_predicts(&v, "foo"));
     }
 
     #[test]
@@ -267,4 +820,377 @@ mod test {
         assert!(!AlphanumSymbol.verify("foobar00"));
         assert!(!AlphanumSymbol.verify("~!@#$%"));
     }
+
+    fn diceware(separator: &str, capitalize: bool, digit: bool) -> Diceware {
+        Diceware {
+            separator: separator.to_string(),
+            capitalize,
+            digit,
+        }
+    }
+
+    #[test]
+    fn test_eff_wordlist() {
+        let words = wordlist();
+        assert_eq!(words.len(), 7776);
+        assert_eq!(words[0], "abacus");
+        assert_eq!(words[7775], "zoom");
+    }
+
+    #[test]
+    fn test_diceware_generate() {
+        let pass = diceware(" ", false, false).generate(6);
+        let words = pass.split(' ').collect::<Vec<_>>();
+        assert_eq!(words.len(), 6);
+        assert!(words.iter().all(|w| wordlist().contains(w)));
+
+        let pass = diceware(" ", true, true).generate(4);
+        let words = pass.split(' ').collect::<Vec<_>>();
+        assert_eq!(words.len(), 4);
+        assert!(words.iter().all(|w| w.starts_with(char::is_uppercase)));
+        assert_eq!(pass.chars().filter(|c| c.is_ascii_digit()).count(), 1);
+    }
+
+    #[test]
+    fn test_diceware_entropy() {
+        let entropy = diceware("-", false, false).entropy(6);
+        assert!((entropy - 77.55).abs() < 0.01);
+        let entropy = diceware("-", true, true).entropy(6);
+        assert!((entropy - 77.55 - 60f64.log2()).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_diceware_minimum_words() {
+        assert!(PwGen::words(2, diceware("-", false, false)).is_err());
+        assert!(PwGen::words(3, diceware("-", false, false)).is_ok());
+    }
+
+    #[test]
+    fn test_charset_parse() {
+        let charset = Charset::parse("[:digit:]a-cx-").unwrap();
+        assert_eq!(charset.chars.iter().collect::<String>(), "-0123456789abcx");
+        assert_eq!(
+            Charset::parse("[:alnum:][:punct:]").unwrap().chars.len(),
+            94
+        );
+        assert!(Charset::parse("[:foo:]").is_err());
+        assert!(Charset::parse("[:alnum").is_err());
+        assert!(Charset::parse("").is_err());
+    }
+
+    #[test]
+    fn test_charset_verify_present_classes() {
+        let charset = Charset::parse("a-f0-9").unwrap();
+        assert_eq!(charset.minimum_length(), 2);
+        assert!(charset.verify("a0"));
+        assert!(!charset.verify("abc"));
+        let charset = Charset::parse("[:alnum:]!?").unwrap();
+        assert_eq!(charset.minimum_length(), 4);
+        assert!(charset.verify("aB3!"));
+        assert!(!charset.verify("aB34"));
+    }
+
+    #[test]
+    fn test_charset_options() {
+        let opts = PwGenOptions {
+            length: 64,
+            no_ambiguous: true,
+            exclude: Some("abc".to_string()),
+            ..Default::default()
+        };
+        let pass = PwGen::from_options(&opts).unwrap().try_generate().unwrap();
+        assert_eq!(pass.len(), 64);
+        assert!(!pass.contains(|c| "01IOlo|abc".contains(c)));
+        assert!(pass.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        let opts = PwGenOptions {
+            length: 8,
+            charset: Some("[:digit:]".to_string()),
+            exclude: Some("0123456789".to_string()),
+            ..Default::default()
+        };
+        assert!(PwGen::from_options(&opts).is_err());
+    }
+
+    // Chi-square statistic of observed counts against expected counts.
+    fn chi_square(observed: &[f64], expected: &[f64]) -> f64 {
+        observed
+            .iter()
+            .zip(expected)
+            .map(|(o, e)| (o - e) * (o - e) / e)
+            .sum()
+    }
+
+    #[test]
+    fn test_generate_short_never_fails() {
+        for _i in 0..10000 {
+            let pass = AlphanumSymbol.try_generate(4).unwrap();
+            assert!(AlphanumSymbol.verify(&pass));
+            let pass = Alphanum.try_generate(3).unwrap();
+            assert!(Alphanum.verify(&pass));
+        }
+        assert_eq!(AlphanumSymbol.try_generate(3), None);
+    }
+
+    #[test]
+    fn test_generate_char_uniformity() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let (count, len) = (5000, 16);
+        let chars = Alphanum
+            .required_classes()
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        let mut observed = vec![0.0; chars.len()];
+        for _i in 0..count {
+            for c in Alphanum.generate_with(len, &mut rng).unwrap().chars() {
+                observed[chars.iter().position(|x| *x == c).unwrap()] += 1.0;
+            }
+        }
+        // One character from each class, and the rest uniformly from all 62 characters.
+        let expected = chars
+            .iter()
+            .map(|c| {
+                let class_len = if c.is_ascii_digit() { 10.0 } else { 26.0 };
+                count as f64 * (1.0 / class_len + (len - 3) as f64 / 62.0)
+            })
+            .collect::<Vec<_>>();
+        // 99.9 percentile of chi-square distribution with 61 degrees of freedom.
+        assert!(chi_square(&observed, &expected) < 100.9);
+    }
+
+    #[test]
+    fn test_generate_position_uniformity() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let count = 4000;
+        let classes = AlphanumSymbol.required_classes();
+        let mut observed = vec![0.0; 16];
+        for _i in 0..count {
+            let pass = AlphanumSymbol.generate_with(4, &mut rng).unwrap();
+            for (pos, c) in pass.chars().enumerate() {
+                let class = classes.iter().position(|chars| chars.contains(&c)).unwrap();
+                observed[pos * 4 + class] += 1.0;
+            }
+        }
+        // Every class appears exactly once, at each position with equal probability.
+        let expected = vec![count as f64 / 4.0; 16];
+        // 99.9 percentile of chi-square distribution with 12 degrees of freedom.
+        assert!(chi_square(&observed, &expected) < 32.9);
+    }
+
+    #[test]
+    fn test_generate_rules_max_consecutive() {
+        let rules = "required: [a]; allowed: [b]; max-consecutive: 1"
+            .parse::<PasswordRules>()
+            .unwrap();
+        for _i in 0..1000 {
+            let pass = rules.try_generate(6).unwrap();
+            assert!(pass == "ababab" || pass == "bababa");
+        }
+        let rules = "required: digit; required: upper; allowed: [x]; max-consecutive: 2"
+            .parse::<PasswordRules>()
+            .unwrap();
+        for _i in 0..1000 {
+            assert!(rules.verify(&rules.try_generate(8).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_rules_with_charset_options() {
+        let opts = PwGenOptions {
+            length: 12,
+            rules: Some("required: digit".to_string()),
+            ..Default::default()
+        };
+        assert!(PwGen::from_options(&opts).is_ok());
+        for opts in [
+            PwGenOptions {
+                symbol: true,
+                ..opts.clone()
+            },
+            PwGenOptions {
+                charset: Some("[:digit:]".to_string()),
+                ..opts.clone()
+            },
+            PwGenOptions {
+                exclude: Some("0".to_string()),
+                ..opts.clone()
+            },
+        ] {
+            assert!(PwGen::from_options(&opts).is_err());
+        }
+    }
+
+    #[test]
+    fn test_conflicting_options() {
+        let opts = PwGenOptions {
+            length: 12,
+            ..Default::default()
+        };
+        for opts in [
+            PwGenOptions {
+                pin: true,
+                words: Some(3),
+                ..opts.clone()
+            },
+            PwGenOptions {
+                pin: true,
+                pronounceable: true,
+                ..opts.clone()
+            },
+            PwGenOptions {
+                pronounceable: true,
+                rules: Some("required: digit".to_string()),
+                ..opts.clone()
+            },
+            PwGenOptions {
+                pin: true,
+                symbol: true,
+                ..opts.clone()
+            },
+            PwGenOptions {
+                words: Some(3),
+                charset: Some("a-z".to_string()),
+                ..opts.clone()
+            },
+            PwGenOptions {
+                pronounceable: true,
+                no_ambiguous: true,
+                ..opts.clone()
+            },
+            PwGenOptions {
+                capitalize: true,
+                ..opts.clone()
+            },
+            PwGenOptions {
+                pin: true,
+                separator: Some(".".to_string()),
+                ..opts.clone()
+            },
+        ] {
+            assert!(matches!(
+                PwGen::from_options(&opts),
+                Err(AikotError::ConflictingOptions { .. })
+            ));
+        }
+        let words = PwGenOptions {
+            words: Some(3),
+            separator: Some(".".to_string()),
+            capitalize: true,
+            digit: true,
+            ..opts.clone()
+        };
+        assert!(PwGen::from_options(&words).is_ok());
+    }
+
+    #[test]
+    fn test_username_options() {
+        let words = PwGenOptions {
+            length: 12,
+            words: Some(2),
+            separator: Some(".".to_string()),
+            ..Default::default()
+        };
+        assert!(words.check_username().is_ok());
+        assert!(words.check_email_alias().is_err());
+        for opts in [
+            PwGenOptions {
+                pin: true,
+                ..words.clone()
+            },
+            PwGenOptions {
+                symbol: true,
+                ..words.clone()
+            },
+            PwGenOptions {
+                charset: Some("a-z".to_string()),
+                ..words.clone()
+            },
+            PwGenOptions {
+                pronounceable: true,
+                ..words.clone()
+            },
+        ] {
+            assert!(opts.check_username().is_err());
+            assert!(opts.check_email_alias().is_err());
+        }
+        let none = PwGenOptions {
+            length: 12,
+            ..Default::default()
+        };
+        assert!(none.check_username().is_ok());
+        assert!(none.check_email_alias().is_ok());
+    }
+
+    #[test]
+    fn test_template_rules_with_charset() {
+        // The configured character set is not applied over the rules of a template.
+        let mut opts = PwGenOptions {
+            length: 12,
+            ..Default::default()
+        };
+        opts.default_rules(Some("required: digit"));
+        assert!(!opts.uses_configured_charset());
+        assert!(matches!(PwGen::from_options(&opts), Ok(PwGen::Rules(..))));
+
+        // Character set options given explicitly override the rules of a template.
+        for mut opts in [
+            PwGenOptions {
+                length: 12,
+                symbol: true,
+                ..Default::default()
+            },
+            PwGenOptions {
+                length: 12,
+                exclude: Some("0".to_string()),
+                ..Default::default()
+            },
+        ] {
+            opts.default_rules(Some("required: digit"));
+            assert!(opts.rules.is_none());
+            assert!(PwGen::from_options(&opts).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_koremutake_syllables() {
+        let mut syllables = KOREMUTAKE_SYLLABLES.to_vec();
+        assert_eq!(syllables[0], "ba");
+        assert_eq!(syllables[12], "fe");
+        assert_eq!(syllables[89], "bra");
+        assert_eq!(syllables[127], "tri");
+        syllables.sort_unstable();
+        syllables.dedup();
+        assert_eq!(syllables.len(), 128);
+    }
+
+    #[test]
+    fn test_pronounceable_generate() {
+        for len in 4..16 {
+            let pass = Pronounceable.try_generate(len).unwrap();
+            assert_eq!(pass.len(), len);
+            assert!(pass.chars().all(|c| c.is_ascii_lowercase()));
+        }
+    }
+
+    #[test]
+    fn test_pin_verify() {
+        assert!(Pin.verify("2580"));
+        assert!(Pin.verify("1359"));
+        assert!(!Pin.verify("1234"));
+        assert!(!Pin.verify("9870"));
+        assert!(!Pin.verify("0007"));
+        assert!(!Pin.verify("1212"));
+        assert!(!Pin.verify("123123"));
+        assert!(!Pin.verify("12a4"));
+    }
+
+    #[test]
+    fn test_pin_generate() {
+        for _i in 0..10000 {
+            let pin = Pin.try_generate(4).unwrap();
+            assert_eq!(pin.len(), 4);
+            assert!(Pin.verify(&pin));
+        }
+    }
 }
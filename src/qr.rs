@@ -0,0 +1,108 @@
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::Error;
+use image::{GrayImage, ImageFormat, Luma};
+use qrcode::render::{svg, unicode};
+use qrcode::{Color, QrCode};
+
+use crate::err::AikotError;
+use crate::io::create_private_file;
+
+const QUIET_ZONE: u32 = 4;
+const MODULE_PIXELS: u32 = 8;
+
+// Decode all QR codes found in a PNG or JPEG image.
+pub fn decode_image(path: &Path) -> Result<Vec<String>, Error> {
+    let contents = decode(image::open(path)?.to_luma8());
+    if contents.is_empty() {
+        Err(AikotError::QrCodeNotFound {
+            path: format!("{}", path.display()),
+        }
+        .into())
+    } else {
+        Ok(contents)
+    }
+}
+
+// Light modules are drawn as blocks, which suits terminals with dark background.
+pub fn render_terminal(data: &str) -> Result<String, Error> {
+    let code = QrCode::new(data)?;
+    Ok(code
+        .render::<unicode::Dense1x2>()
+        .dark_color(unicode::Dense1x2::Light)
+        .light_color(unicode::Dense1x2::Dark)
+        .build())
+}
+
+// Write SVG for ".svg" path, otherwise the image format is chosen by extension.
+pub fn write_image(data: &str, path: &Path) -> Result<(), Error> {
+    let is_svg = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        let code = QrCode::new(data)?;
+        let image = code.render::<svg::Color>().min_dimensions(200, 200).build();
+        create_private_file(path)?.write_all(image.as_bytes())?;
+    } else {
+        let format = ImageFormat::from_path(path)?;
+        let mut writer = BufWriter::new(create_private_file(path)?);
+        to_image(data)?.write_to(&mut writer, format)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+fn decode(image: GrayImage) -> Vec<String> {
+    let mut prepared = rqrr::PreparedImage::prepare(image);
+    prepared
+        .detect_grids()
+        .iter()
+        .filter_map(|grid| grid.decode().ok())
+        .map(|(_, content)| content)
+        .collect()
+}
+
+fn to_image(data: &str) -> Result<GrayImage, Error> {
+    let code = QrCode::new(data)?;
+    let width = code.width() as u32;
+    let colors = code.to_colors();
+    let size = (width + QUIET_ZONE * 2) * MODULE_PIXELS;
+    Ok(GrayImage::from_fn(size, size, |x, y| {
+        let mx = (x / MODULE_PIXELS)
+            .checked_sub(QUIET_ZONE)
+            .filter(|m| *m < width);
+        let my = (y / MODULE_PIXELS)
+            .checked_sub(QUIET_ZONE)
+            .filter(|m| *m < width);
+        let dark = mx
+            .zip(my)
+            .is_some_and(|(mx, my)| colors[(my * width + mx) as usize] == Color::Dark);
+        if dark {
+            Luma([0])
+        } else {
+            Luma([255])
+        }
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn image_round_trip() {
+        let uri = "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP&issuer=Example";
+        assert_eq!(decode(to_image(uri).unwrap()), vec![uri]);
+        assert!(decode(GrayImage::new(64, 64)).is_empty());
+    }
+
+    #[test]
+    fn render_half_blocks() {
+        let rendered = render_terminal("secret").unwrap();
+        let lines = rendered.lines().collect::<Vec<_>>();
+        // Version 1 code is 21 modules with 4 modules quiet zone, 2 rows per line.
+        assert_eq!(lines.len(), (21 + 8_usize).div_ceil(2));
+        assert!(lines.iter().all(|l| l.chars().count() == 21 + 8));
+    }
+}
@@ -0,0 +1,299 @@
+use std::cmp::{max, min};
+use std::str::FromStr;
+
+use rand::distributions::Distribution;
+use rand::Rng;
+
+use crate::err::AikotError;
+use crate::password::{class_entropy, construct, CharClass, PasswordClass};
+
+// Special characters of the passwordrules spec, without space to keep passwords easy to paste.
+const SPECIAL_CHARS: &str = "-~!@#$%^&*_+=`|(){}[:;\"'<>,.?]/\\";
+
+// Site requirements in the passwordrules syntax,
+// e.g. "required: upper; required: digit; allowed: [-_]; max-consecutive: 2; minlength: 12".
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PasswordRules {
+    required: Vec<Vec<char>>,
+    allowed: Vec<char>,
+    // Union of required and allowed characters, computed once when parsed.
+    chars: Vec<char>,
+    max_consecutive: Option<usize>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+}
+
+impl PasswordRules {
+    // Fit the requested length into minlength and maxlength.
+    pub fn length(&self, length: usize) -> usize {
+        let length = max(length, self.min_length.unwrap_or(0));
+        min(length, self.max_length.unwrap_or(usize::MAX))
+    }
+}
+
+impl PasswordRules {
+    // Choose characters one by one, avoiding runs longer than max-consecutive
+    // and using the remaining positions for required classes not met yet.
+    fn generate_sequential<R>(&self, len: usize, max_run: usize, rng: &mut R) -> Option<String>
+    where
+        R: Rng,
+    {
+        let mut unmet = self.required.iter().collect::<Vec<_>>();
+        let mut pass = Vec::<char>::with_capacity(len);
+        let mut run = 0;
+        for i in 0..len {
+            let banned = pass.last().filter(|_| run >= max_run);
+            let pool: Vec<char> = if len - i <= unmet.len() {
+                unmet
+                    .iter()
+                    .flat_map(|chars| chars.iter())
+                    .copied()
+                    .collect()
+            } else {
+                self.chars.clone()
+            };
+            let candidates = pool
+                .into_iter()
+                .filter(|c| Some(c) != banned)
+                .collect::<Vec<_>>();
+            if candidates.is_empty() {
+                return None;
+            }
+            let c = candidates[rng.gen_range(0..candidates.len())];
+            run = if pass.last() == Some(&c) { run + 1 } else { 1 };
+            pass.push(c);
+            unmet.retain(|chars| !chars.contains(&c));
+        }
+        if unmet.is_empty() {
+            Some(pass.into_iter().collect())
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for PasswordRules {
+    type Err = AikotError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = PasswordRules::default();
+        for property in split_outside_brackets(s, ';') {
+            let property = property.trim();
+            if property.is_empty() {
+                continue;
+            }
+            let (name, value) = property
+                .split_once(':')
+                .ok_or_else(|| invalid(&format!("missing value: {}", property)))?;
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "required" => rules.required.push(parse_classes(value)?),
+                "allowed" => rules.allowed.extend(parse_classes(value)?),
+                "max-consecutive" => {
+                    let n = parse_number(name, value)?;
+                    rules.max_consecutive = Some(rules.max_consecutive.map_or(n, |m| min(m, n)));
+                }
+                "minlength" => {
+                    let n = parse_number(name, value)?;
+                    rules.min_length = Some(rules.min_length.map_or(n, |m| max(m, n)));
+                }
+                "maxlength" => {
+                    let n = parse_number(name, value)?;
+                    rules.max_length = Some(rules.max_length.map_or(n, |m| min(m, n)));
+                }
+                _ => return Err(invalid(&format!("unknown property: {}", name.trim()))),
+            }
+        }
+        rules.required.iter_mut().for_each(|chars| {
+            chars.sort_unstable();
+            chars.dedup();
+        });
+        // Ascii printable if nothing is specified.
+        rules.chars = rules
+            .required
+            .iter()
+            .flatten()
+            .chain(rules.allowed.iter())
+            .copied()
+            .collect();
+        if rules.chars.is_empty() {
+            rules.chars = ('!'..='~').collect();
+        }
+        rules.chars.sort_unstable();
+        rules.chars.dedup();
+        Ok(rules)
+    }
+}
+
+impl Distribution<char> for PasswordRules {
+    fn sample<R>(&self, rng: &mut R) -> char
+    where
+        R: rand::Rng + ?Sized,
+    {
+        self.chars[rng.gen_range(0..self.chars.len())]
+    }
+}
+
+impl CharClass for PasswordRules {
+    fn required_classes(&self) -> Vec<Vec<char>> {
+        self.required.clone()
+    }
+}
+
+impl PasswordClass for PasswordRules {
+    fn generate_with<R>(&self, len: usize, rng: &mut R) -> Option<String>
+    where
+        R: Rng,
+    {
+        match self.max_consecutive {
+            Some(max_run) => self.generate_sequential(len, max_run, rng),
+            None => construct(self, len, rng),
+        }
+    }
+
+    fn entropy(&self, len: usize) -> f64 {
+        class_entropy(&self.required, self.chars.len(), len)
+    }
+
+    fn minimum_length(&self) -> usize {
+        max(self.required.len(), self.min_length.unwrap_or(0)).max(1)
+    }
+
+    fn verify(&self, pass: &str) -> bool {
+        let len = pass.chars().count();
+        len >= self.min_length.unwrap_or(0)
+            && len <= self.max_length.unwrap_or(usize::MAX)
+            && pass.chars().all(|c| self.chars.binary_search(&c).is_ok())
+            && self
+                .required
+                .iter()
+                .all(|chars| pass.chars().any(|c| chars.contains(&c)))
+            && self.max_consecutive.is_none_or(|n| longest_run(pass) <= n)
+    }
+}
+
+fn longest_run(pass: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev = None;
+    for c in pass.chars() {
+        run = if prev == Some(c) { run + 1 } else { 1 };
+        longest = max(longest, run);
+        prev = Some(c);
+    }
+    longest
+}
+
+// Separators inside custom classes like "[;,]" are part of the class.
+fn split_outside_brackets(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_bracket = false;
+    let mut start = 0;
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if in_bracket {
+            let next = chars.peek().map(|(_, c)| *c);
+            let closing = next.is_none_or(|n| n == ',' || n == ';' || n.is_whitespace());
+            if c == ']' && closing {
+                in_bracket = false;
+            }
+        } else if c == '[' {
+            in_bracket = true;
+        } else if c == sep {
+            parts.push(&s[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn parse_classes(value: &str) -> Result<Vec<char>, AikotError> {
+    let mut chars = Vec::new();
+    for class in split_outside_brackets(value, ',') {
+        let class = class.trim();
+        if let Some(custom) = class.strip_prefix('[').and_then(|c| c.strip_suffix(']')) {
+            if custom.is_empty() {
+                return Err(invalid("empty character class: []"));
+            }
+            chars.extend(custom.chars());
+            continue;
+        }
+        match class.to_ascii_lowercase().as_str() {
+            "upper" => chars.extend('A'..='Z'),
+            "lower" => chars.extend('a'..='z'),
+            "digit" => chars.extend('0'..='9'),
+            "special" => chars.extend(SPECIAL_CHARS.chars()),
+            "ascii-printable" | "unicode" => chars.extend('!'..='~'),
+            _ => return Err(invalid(&format!("unknown character class: {}", class))),
+        }
+    }
+    Ok(chars)
+}
+
+fn parse_number(name: &str, value: &str) -> Result<usize, AikotError> {
+    value
+        .parse()
+        .map_err(|_| invalid(&format!("{} must be number: {}", name.trim(), value)))
+}
+
+fn invalid(reason: &str) -> AikotError {
+    AikotError::InvalidPasswordRules {
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_rules() {
+        let rules: PasswordRules =
+            "required: upper; required: digit; allowed: [-_]; max-consecutive: 2; minlength: 12; maxlength: 20"
+                .parse()
+                .unwrap();
+        assert_eq!(rules.required.len(), 2);
+        assert_eq!(rules.required[0].len(), 26);
+        assert_eq!(rules.allowed, vec!['-', '_']);
+        assert_eq!(rules.max_consecutive, Some(2));
+        assert_eq!(rules.length(8), 12);
+        assert_eq!(rules.length(24), 20);
+        assert_eq!(rules.chars.len(), 26 + 10 + 2);
+    }
+
+    #[test]
+    fn parse_custom_classes() {
+        let rules: PasswordRules = "required: lower, [;,]; allowed: [-]]; MINLENGTH: 8"
+            .parse()
+            .unwrap();
+        assert_eq!(rules.required.len(), 1);
+        assert_eq!(rules.required[0].len(), 26 + 2);
+        assert!(rules.required[0].contains(&';'));
+        assert_eq!(rules.allowed, vec!['-', ']']);
+        assert_eq!(rules.min_length, Some(8));
+    }
+
+    #[test]
+    fn parse_invalid_rules() {
+        assert!("required: vowel".parse::<PasswordRules>().is_err());
+        assert!("minlength: twelve".parse::<PasswordRules>().is_err());
+        assert!("maximum: 3".parse::<PasswordRules>().is_err());
+        assert!("required".parse::<PasswordRules>().is_err());
+        assert!("allowed: []".parse::<PasswordRules>().is_err());
+        assert_eq!("".parse::<PasswordRules>().unwrap().chars.len(), 94);
+    }
+
+    #[test]
+    fn verify_rules() {
+        let rules: PasswordRules =
+            "required: upper; required: digit; max-consecutive: 2; maxlength: 6"
+                .parse()
+                .unwrap();
+        assert!(rules.verify("AB12CD"));
+        assert!(!rules.verify("ABCDEF"));
+        assert!(!rules.verify("AAA123"));
+        assert!(!rules.verify("AB12CDE"));
+        assert!(!rules.verify("ab12CD"));
+    }
+}
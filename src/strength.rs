@@ -0,0 +1,552 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::password::wordlist;
+
+// Ordered by popularity, the index is used as rank.
+const COMMON_PASSWORDS: [&str; 64] = [
+    "123456",
+    "password",
+    "12345678",
+    "qwerty",
+    "123456789",
+    "12345",
+    "1234",
+    "111111",
+    "1234567",
+    "dragon",
+    "123123",
+    "baseball",
+    "abc123",
+    "football",
+    "monkey",
+    "letmein",
+    "696969",
+    "shadow",
+    "master",
+    "666666",
+    "qwertyuiop",
+    "123321",
+    "mustang",
+    "1234567890",
+    "michael",
+    "654321",
+    "superman",
+    "1qaz2wsx",
+    "7777777",
+    "121212",
+    "000000",
+    "qazwsx",
+    "123qwe",
+    "killer",
+    "trustno1",
+    "jordan",
+    "jennifer",
+    "zxcvbnm",
+    "asdfgh",
+    "hunter",
+    "buster",
+    "soccer",
+    "harley",
+    "batman",
+    "andrew",
+    "tigger",
+    "sunshine",
+    "iloveyou",
+    "charlie",
+    "robert",
+    "thomas",
+    "hockey",
+    "ranger",
+    "daniel",
+    "starwars",
+    "computer",
+    "michelle",
+    "jessica",
+    "pepper",
+    "freedom",
+    "princess",
+    "welcome",
+    "admin",
+    "login",
+];
+const KEYBOARD_ROWS: [(&str, f64); 4] = [
+    ("1234567890-=", 0.0),
+    ("qwertyuiop[]", 0.5),
+    ("asdfghjkl;'", 0.75),
+    ("zxcvbnm,./", 1.25),
+];
+const SCORE_LABELS: [&str; 5] = ["very weak", "weak", "fair", "strong", "very strong"];
+// Bits of 10^3, 10^6, 10^8 and 10^10 guesses as score thresholds of zxcvbn.
+const SCORE_THRESHOLDS: [f64; 4] = [9.97, 19.93, 26.58, 33.22];
+const MIN_MATCH_LEN: usize = 3;
+const MAX_WORD_LEN: usize = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pattern {
+    Common,
+    Date,
+    Dictionary,
+    KeyboardWalk,
+    Repeat,
+    Sequence,
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Pattern::Common => "common password",
+            Pattern::Date => "date",
+            Pattern::Dictionary => "dictionary word",
+            Pattern::KeyboardWalk => "keyboard walk",
+            Pattern::Repeat => "repeat",
+            Pattern::Sequence => "sequence",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Match {
+    pub pattern: Pattern,
+    pub start: usize,
+    pub end: usize,
+    bits: f64,
+}
+
+pub struct Strength {
+    pub entropy: f64,
+    pub matches: Vec<Match>,
+}
+
+impl Strength {
+    // Score from 0 to 4 as zxcvbn.
+    pub fn score(&self) -> usize {
+        SCORE_THRESHOLDS
+            .iter()
+            .take_while(|t| self.entropy >= **t)
+            .count()
+    }
+
+    pub fn label(&self) -> &'static str {
+        SCORE_LABELS[self.score()]
+    }
+}
+
+// Estimate entropy by the cheapest way to guess the password
+// as a sequence of patterns and bruteforced characters.
+pub fn estimate(pass: &str) -> Strength {
+    let chars = pass.chars().collect::<Vec<_>>();
+    estimate_chars(&chars, 0, Dictionary::get(), &mut HashMap::new())
+}
+
+struct Dictionary {
+    ranks: HashMap<&'static str, usize>,
+    words: usize,
+}
+
+impl Dictionary {
+    // Built once, since audit estimates every password of the store.
+    fn get() -> &'static Self {
+        static DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+        DICTIONARY.get_or_init(Dictionary::new)
+    }
+
+    fn new() -> Self {
+        let words = wordlist();
+        let len = words.len();
+        let ranks = COMMON_PASSWORDS
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (*w, i + 1))
+            .chain(words.iter().map(|w| (*w, 0)))
+            .fold(HashMap::new(), |mut map, (w, rank)| {
+                map.entry(w).or_insert(rank);
+                map
+            });
+        Dictionary { ranks, words: len }
+    }
+
+    // Rank 0 is a word of dictionary, otherwise a common password.
+    fn bits(&self, word: &str) -> Option<(Pattern, f64)> {
+        self.ranks.get(word).map(|rank| match rank {
+            0 => (Pattern::Dictionary, (self.words as f64).log2()),
+            rank => (Pattern::Common, ((rank + 1) as f64).log2()),
+        })
+    }
+}
+
+// The offset locates chars in the whole password, for the memo of repeated blocks.
+fn estimate_chars(
+    chars: &[char],
+    offset: usize,
+    dictionary: &Dictionary,
+    memo: &mut HashMap<(usize, usize), f64>,
+) -> Strength {
+    let n = chars.len();
+    let char_bits = (cardinality(chars) as f64).log2();
+    let mut candidates = Vec::new();
+    candidates.extend(dictionary_matches(chars, dictionary));
+    candidates.extend(keyboard_matches(chars));
+    candidates.extend(sequence_matches(chars));
+    candidates.extend(repeat_matches(chars, offset, dictionary, memo));
+    candidates.extend(date_matches(chars));
+
+    // best[i] is the minimum bits of the first i characters with the last match.
+    let mut best: Vec<(f64, Option<Match>)> = vec![(0.0, None); n + 1];
+    for i in 1..=n {
+        best[i] = (best[i - 1].0 + char_bits, None);
+        for m in candidates.iter().filter(|m| m.end == i) {
+            let bits = best[m.start].0 + m.bits;
+            if bits < best[i].0 {
+                best[i] = (bits, Some(m.clone()));
+            }
+        }
+    }
+    let mut matches = Vec::new();
+    let mut i = n;
+    while i > 0 {
+        match &best[i].1 {
+            Some(m) => {
+                i = m.start;
+                matches.push(m.clone());
+            }
+            None => i -= 1,
+        }
+    }
+    matches.reverse();
+    Strength {
+        entropy: best[n].0,
+        matches,
+    }
+}
+
+// Size of the character classes used in the password.
+fn cardinality(chars: &[char]) -> usize {
+    let has = |pred: fn(&char) -> bool, size: usize| {
+        if chars.iter().any(pred) {
+            size
+        } else {
+            0
+        }
+    };
+    let ascii = has(char::is_ascii_lowercase, 26)
+        + has(char::is_ascii_uppercase, 26)
+        + has(char::is_ascii_digit, 10)
+        + has(char::is_ascii_punctuation, 33);
+    let other = if chars.iter().any(|c| !c.is_ascii_graphic()) {
+        100
+    } else {
+        0
+    };
+    (ascii + other).max(1)
+}
+
+fn dictionary_matches(chars: &[char], dictionary: &Dictionary) -> Vec<Match> {
+    let mut matches = Vec::new();
+    for start in 0..chars.len() {
+        for end in start + MIN_MATCH_LEN..=chars.len().min(start + MAX_WORD_LEN) {
+            let token = &chars[start..end];
+            let lower = token
+                .iter()
+                .map(|c| c.to_ascii_lowercase())
+                .collect::<String>();
+            let unleet = lower.chars().map(unleet).collect::<String>();
+            let leet_count = lower
+                .chars()
+                .zip(unleet.chars())
+                .filter(|(a, b)| a != b)
+                .count();
+            let found = dictionary
+                .bits(&lower)
+                .map(|found| (found, 0))
+                .or_else(|| dictionary.bits(&unleet).map(|found| (found, leet_count)));
+            if let Some(((pattern, bits), leet_count)) = found {
+                let bits = bits + case_bits(token) + leet_count as f64;
+                matches.push(Match {
+                    pattern,
+                    start,
+                    end,
+                    bits,
+                });
+            }
+        }
+    }
+    matches
+}
+
+// Capitalized or all upper words cost a bit, otherwise a bit for each upper character.
+fn case_bits(token: &[char]) -> f64 {
+    let upper = token.iter().filter(|c| c.is_ascii_uppercase()).count();
+    let letters = token.iter().filter(|c| c.is_ascii_alphabetic()).count();
+    if upper == 0 {
+        0.0
+    } else if upper == letters || (upper == 1 && token[0].is_ascii_uppercase()) {
+        1.0
+    } else {
+        upper as f64
+    }
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '@' | '4' => 'a',
+        '3' => 'e',
+        '1' | '!' => 'i',
+        '0' => 'o',
+        '$' | '5' => 's',
+        '7' | '+' => 't',
+        c => c,
+    }
+}
+
+fn key_position(c: char) -> Option<(f64, f64)> {
+    let c = c.to_ascii_lowercase();
+    KEYBOARD_ROWS
+        .iter()
+        .enumerate()
+        .find_map(|(row, (keys, offset))| {
+            keys.chars()
+                .position(|k| k == c)
+                .map(|col| (row as f64, col as f64 + offset))
+        })
+}
+
+fn adjacent_keys(a: char, b: char) -> bool {
+    match (key_position(a), key_position(b)) {
+        (Some((r1, x1)), Some((r2, x2))) => {
+            let (dr, dx) = ((r1 - r2).abs(), (x1 - x2).abs());
+            (dr == 0.0 && dx == 1.0) || (dr == 1.0 && dx <= 1.0)
+        }
+        _ => false,
+    }
+}
+
+// Every part of a walk on QWERTY keyboard, starting from any key with about 4 neighbors.
+fn keyboard_matches(chars: &[char]) -> Vec<Match> {
+    run_matches(
+        chars,
+        |a, b, _| adjacent_keys(a, b),
+        |len| 47f64.log2() + (len - 1) as f64 * 2.0,
+    )
+    .into_iter()
+    .map(|(start, end, bits)| Match {
+        pattern: Pattern::KeyboardWalk,
+        start,
+        end,
+        bits,
+    })
+    .collect()
+}
+
+// Letters or digits in ascending or descending order like "abc" or "987".
+fn sequence_matches(chars: &[char]) -> Vec<Match> {
+    let step = |a: char, b: char| b as i32 - a as i32;
+    let same_class = |a: char, b: char| {
+        (a.is_ascii_lowercase() && b.is_ascii_lowercase())
+            || (a.is_ascii_uppercase() && b.is_ascii_uppercase())
+            || (a.is_ascii_digit() && b.is_ascii_digit())
+    };
+    run_matches(
+        chars,
+        |a, b, prev| {
+            let delta = step(a, b);
+            same_class(a, b) && delta.abs() == 1 && prev.is_none_or(|p| step(p, a) == delta)
+        },
+        |len| 26f64.log2() + (len as f64).log2() + 1.0,
+    )
+    .into_iter()
+    .map(|(start, end, bits)| Match {
+        pattern: Pattern::Sequence,
+        start,
+        end,
+        bits,
+    })
+    .collect()
+}
+
+// Sub-runs of at least MIN_MATCH_LEN characters where each pair satisfies the predicate.
+fn run_matches<P, B>(chars: &[char], pred: P, bits: B) -> Vec<(usize, usize, f64)>
+where
+    P: Fn(char, char, Option<char>) -> bool,
+    B: Fn(usize) -> f64,
+{
+    let mut matches = Vec::new();
+    for start in 0..chars.len() {
+        let mut end = start + 1;
+        while end < chars.len() {
+            let prev = (end >= start + 2).then(|| chars[end - 2]);
+            if !pred(chars[end - 1], chars[end], prev) {
+                break;
+            }
+            end += 1;
+            if end - start >= MIN_MATCH_LEN {
+                matches.push((start, end, bits(end - start)));
+            }
+        }
+    }
+    matches
+}
+
+// Repeated blocks like "aaa" or "abcabc", costing the block and the count.
+// Only the longest run at each start is taken, with the shortest block of it,
+// and the cost of each block is estimated once since blocks nest.
+fn repeat_matches(
+    chars: &[char],
+    offset: usize,
+    dictionary: &Dictionary,
+    memo: &mut HashMap<(usize, usize), f64>,
+) -> Vec<Match> {
+    let mut matches = Vec::new();
+    for start in 0..chars.len() {
+        let longest = (1..=(chars.len() - start) / 2)
+            .map(|block_len| {
+                let block = &chars[start..start + block_len];
+                let count = chars[start..]
+                    .chunks(block_len)
+                    .take_while(|chunk| *chunk == block)
+                    .count();
+                (block_len, count)
+            })
+            .filter(|(block_len, count)| *count >= 2 && block_len * count >= MIN_MATCH_LEN)
+            .max_by_key(|(block_len, count)| (block_len * count, Reverse(*block_len)));
+        if let Some((block_len, count)) = longest {
+            let key = (offset + start, block_len);
+            let block_bits = match memo.get(&key) {
+                Some(bits) => *bits,
+                None => {
+                    let block = &chars[start..start + block_len];
+                    let bits = estimate_chars(block, offset + start, dictionary, memo).entropy;
+                    memo.insert(key, bits);
+                    bits
+                }
+            };
+            matches.push(Match {
+                pattern: Pattern::Repeat,
+                start,
+                end: start + block_len * count,
+                bits: block_bits + (count as f64).log2(),
+            });
+        }
+    }
+    matches
+}
+
+// Years from 1900 to 2049, and dates of day, month and year in any common order.
+fn date_matches(chars: &[char]) -> Vec<Match> {
+    let mut matches = Vec::new();
+    for start in 0..chars.len() {
+        for end in start + 4..=chars.len().min(start + 10) {
+            let token = chars[start..end].iter().collect::<String>();
+            if let Some(bits) = date_bits(&token) {
+                matches.push(Match {
+                    pattern: Pattern::Date,
+                    start,
+                    end,
+                    bits,
+                });
+            }
+        }
+    }
+    matches
+}
+
+fn date_bits(token: &str) -> Option<f64> {
+    let parts = match token.chars().find(|c| matches!(c, '/' | '-' | '.')) {
+        Some(sep) => token.split(sep).map(|s| s.to_string()).collect::<Vec<_>>(),
+        None if token.chars().all(|c| c.is_ascii_digit()) => match token.len() {
+            4 => {
+                let year = token.parse::<u32>().ok()?;
+                return (1900..=2049).contains(&year).then(|| 150f64.log2());
+            }
+            6 => vec![token[..2].into(), token[2..4].into(), token[4..].into()],
+            8 if valid_year(&token[..4]) => {
+                vec![token[..4].into(), token[4..6].into(), token[6..].into()]
+            }
+            8 => vec![token[..2].into(), token[2..4].into(), token[4..].into()],
+            _ => return None,
+        },
+        None => return None,
+    };
+    if parts.len() != 3
+        || parts
+            .iter()
+            .any(|p| p.is_empty() || !p.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    let [a, b, c] = [&parts[0], &parts[1], &parts[2]];
+    let valid = (valid_year(a) && valid_day_month(c, b))
+        || (valid_year(c) && (valid_day_month(a, b) || valid_day_month(b, a)));
+    valid.then(|| (365.0 * 200.0f64).log2())
+}
+
+fn valid_year(s: &str) -> bool {
+    match s.len() {
+        2 => true,
+        4 => s.parse::<u32>().is_ok_and(|y| (1900..=2099).contains(&y)),
+        _ => false,
+    }
+}
+
+fn valid_day_month(day: &str, month: &str) -> bool {
+    day.len() <= 2
+        && month.len() <= 2
+        && day.parse::<u32>().is_ok_and(|d| (1..=31).contains(&d))
+        && month.parse::<u32>().is_ok_and(|m| (1..=12).contains(&m))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn patterns(pass: &str) -> Vec<Pattern> {
+        estimate(pass).matches.iter().map(|m| m.pattern).collect()
+    }
+
+    #[test]
+    fn detect_patterns() {
+        assert_eq!(patterns("password"), vec![Pattern::Common]);
+        assert_eq!(patterns("P@ssw0rd"), vec![Pattern::Common]);
+        assert_eq!(patterns("zxcvfr"), vec![Pattern::KeyboardWalk]);
+        assert_eq!(patterns("lmnopq"), vec![Pattern::Sequence]);
+        assert_eq!(patterns("zzzzzzzz"), vec![Pattern::Repeat]);
+        assert_eq!(patterns("19/07/1985"), vec![Pattern::Date]);
+        assert_eq!(
+            patterns("Abacus1985"),
+            vec![Pattern::Dictionary, Pattern::Date]
+        );
+        assert!(patterns("X7#kP2!qL9@z").is_empty());
+    }
+
+    #[test]
+    fn score_passwords() {
+        assert_eq!(estimate("password").score(), 0);
+        assert_eq!(estimate("qwerty123").score(), 0);
+        assert_eq!(estimate("monkey2024!").score(), 1);
+        assert_eq!(estimate("Abacus1985").score(), 2);
+        assert_eq!(estimate("X7#kP2!qL9@z").score(), 4);
+        assert_eq!(estimate("X7#kP2!qL9@z").label(), "very strong");
+        assert_eq!(estimate("").score(), 0);
+    }
+
+    #[test]
+    fn estimate_long_repeats_quickly() {
+        let started = Instant::now();
+        for block in ["a", "ab", "abc", "aab", "abcabd"] {
+            let pass = block.repeat(256 / block.len());
+            assert_eq!(patterns(&pass)[0], Pattern::Repeat);
+        }
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn entropy_of_random_chars() {
+        let strength = estimate("Xk7qP2");
+        assert!((strength.entropy - 6.0 * 62f64.log2()).abs() < 0.01);
+    }
+}
@@ -4,22 +4,53 @@ use std::io::Read;
 use std::path::Path;
 
 use anyhow::Error;
-use gtmpl::error::ExecError;
 use gtmpl::{Context, Template, Value};
 
+use crate::entry::EntryType;
+
+const CARD_TEMPLATE: &str = "{{ .Content }}\ntype: card\nname: \nnumber: \nexpiry: \ncvv: \n";
+const SSH_TEMPLATE: &str = "{{ .Content }}\ntype: ssh\npublic_key: \nprivate_key:\n";
+const WIFI_TEMPLATE: &str = "{{ .Content }}\ntype: wifi\nssid: \nsecurity: WPA\n";
+
 pub struct PassTmpl {
     tmpl: Template,
+    text: String,
+    vars: HashMap<String, String>,
 }
 
 impl PassTmpl {
     pub fn new() -> Self {
         PassTmpl {
             tmpl: Template::default(),
+            text: String::new(),
+            vars: HashMap::new(),
         }
     }
 
+    // Extra variable like .Username, Content and Path are set by render.
+    pub fn set_var(&mut self, key: &str, val: &str) {
+        self.vars.insert(key.to_string(), val.to_string());
+    }
+
+    // Whether the loaded template refers to the variable, e.g. "EmailAlias" for ".EmailAlias".
+    pub fn uses_var(&self, key: &str) -> bool {
+        let var = format!(".{}", key);
+        self.text.match_indices(&var).any(|(i, _)| {
+            !self.text[i + var.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        })
+    }
+
     pub fn load_default(&mut self) -> Result<(), Error> {
-        Ok(self.tmpl.parse("{{ .Content }}\n")?)
+        self.parse("{{ .Content }}\n")
+    }
+
+    pub fn load_type(&mut self, entry_type: EntryType) -> Result<(), Error> {
+        let tmpl = match entry_type {
+            EntryType::Card => CARD_TEMPLATE,
+            EntryType::Ssh => SSH_TEMPLATE,
+            EntryType::Wifi => WIFI_TEMPLATE,
+        };
+        self.parse(tmpl)
     }
 
     pub fn load<P>(&mut self, tmpl_path: P) -> Result<(), Error>
@@ -29,15 +60,24 @@ impl PassTmpl {
         let mut f = File::open(&tmpl_path)?;
         let mut buf = String::new();
         f.read_to_string(&mut buf)?;
-        Ok(self.tmpl.parse(buf)?)
+        self.parse(&buf)
+    }
+
+    fn parse(&mut self, text: &str) -> Result<(), Error> {
+        self.text = text.to_string();
+        Ok(self.tmpl.parse(text)?)
     }
 
-    pub fn render(&self, content: &str, path: &str) -> Result<String, ExecError> {
-        let mut map = HashMap::<String, Value>::new();
+    pub fn render(&self, content: &str, path: &str) -> Result<String, Error> {
+        let mut map = self
+            .vars
+            .iter()
+            .map(|(key, val)| (key.clone(), val.into()))
+            .collect::<HashMap<String, Value>>();
         map.insert("Content".to_string(), content.into());
         map.insert("Path".to_string(), path.into());
         let context = Context::from(map);
-        self.tmpl.render(&context)
+        Ok(self.tmpl.render(&context)?)
     }
 }
 
@@ -57,6 +97,32 @@ mod test {
         assert_eq!(&result.unwrap(), "context\n");
     }
 
+    #[test]
+    fn render_typed() {
+        let mut ptmpl = PassTmpl::new();
+        ptmpl.load_type(EntryType::Wifi).unwrap();
+        assert_eq!(
+            ptmpl.render("secret", "wifi/home").unwrap(),
+            "secret\ntype: wifi\nssid: \nsecurity: WPA\n"
+        );
+    }
+
+    #[test]
+    fn render_vars() {
+        let mut ptmpl = PassTmpl::new();
+        ptmpl
+            .parse("{{ .Content }}\nuser: {{ .Username }}\nemail: {{ .EmailAlias }}\n")
+            .unwrap();
+        assert!(ptmpl.uses_var("EmailAlias"));
+        assert!(!ptmpl.uses_var("Email"));
+        ptmpl.set_var("Username", "glade_snowcap42");
+        ptmpl.set_var("EmailAlias", "me+x7k2@example.com");
+        assert_eq!(
+            ptmpl.render("secret", "example.com").unwrap(),
+            "secret\nuser: glade_snowcap42\nemail: me+x7k2@example.com\n"
+        );
+    }
+
     #[test]
     fn render_from_file() {
         let mut path = env::temp_dir().clone();
@@ -0,0 +1,137 @@
+use std::str::FromStr;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::err::AikotError;
+use crate::password::wordlist;
+
+const HANDLE_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+const ALIAS_TAG_LENGTH: usize = 10;
+
+// Non-identifying handle of random words and two digits, or of random characters.
+pub enum Username {
+    Random(usize),
+    Words(usize, String),
+}
+
+impl Username {
+    pub fn generate<R>(&self, rng: &mut R) -> String
+    where
+        R: Rng,
+    {
+        match self {
+            Username::Random(len) => random_handle(*len, rng),
+            Username::Words(count, separator) => {
+                let wordlist = wordlist();
+                let words = (0..*count)
+                    .map(|_| *wordlist.choose(rng).unwrap_or(&""))
+                    .collect::<Vec<_>>();
+                format!("{}{:02}", words.join(separator), rng.gen_range(0..100))
+            }
+        }
+    }
+}
+
+// Base address of aliases, "user@example.com" for subaddressing
+// or "@example.com" for a catch-all domain.
+#[derive(Debug, PartialEq)]
+pub struct EmailAlias {
+    user: Option<String>,
+    domain: String,
+}
+
+impl EmailAlias {
+    pub fn generate<R>(&self, rng: &mut R) -> String
+    where
+        R: Rng,
+    {
+        let tag = random_handle(ALIAS_TAG_LENGTH, rng);
+        match &self.user {
+            Some(user) => format!("{}+{}@{}", user, tag, self.domain),
+            None => format!("{}@{}", tag, self.domain),
+        }
+    }
+}
+
+impl FromStr for EmailAlias {
+    type Err = AikotError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (user, domain) = s.trim().rsplit_once('@').unwrap_or(("", s.trim()));
+        if domain.is_empty() || !domain.contains('.') || domain.contains(char::is_whitespace) {
+            return Err(AikotError::InvalidConfig {
+                key: "email_alias".to_string(),
+            });
+        }
+        Ok(EmailAlias {
+            user: Some(user).filter(|u| !u.is_empty()).map(|u| u.to_string()),
+            domain: domain.to_string(),
+        })
+    }
+}
+
+// Starts with a letter, since some sites reject usernames and addresses starting with a digit.
+fn random_handle<R>(len: usize, rng: &mut R) -> String
+where
+    R: Rng,
+{
+    (0..len)
+        .map(|i| {
+            let chars = if i == 0 {
+                &HANDLE_CHARS[..26]
+            } else {
+                HANDLE_CHARS
+            };
+            char::from(chars[rng.gen_range(0..chars.len())])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn generate_usernames() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let random = Username::Random(12).generate(&mut rng);
+        assert_eq!(random.len(), 12);
+        assert!(random.starts_with(|c: char| c.is_ascii_lowercase()));
+        assert!(random.bytes().all(|b| HANDLE_CHARS.contains(&b)));
+
+        let words = Username::Words(2, "_".to_string()).generate(&mut rng);
+        let (handle, digits) = words.split_at(words.len() - 2);
+        assert_eq!(handle.split('_').count(), 2);
+        assert!(digits.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn generate_email_aliases() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let alias = "me@example.com".parse::<EmailAlias>().unwrap();
+        let addr = alias.generate(&mut rng);
+        assert!(addr.starts_with("me+"));
+        assert!(addr.ends_with("@example.com"));
+        assert_eq!(addr.len(), "me+@example.com".len() + ALIAS_TAG_LENGTH);
+
+        for base in ["@example.com", "example.com"] {
+            let alias = base.parse::<EmailAlias>().unwrap();
+            let addr = alias.generate(&mut rng);
+            let (tag, domain) = addr.split_once('@').unwrap();
+            assert_eq!(tag.len(), ALIAS_TAG_LENGTH);
+            assert_eq!(domain, "example.com");
+        }
+    }
+
+    #[test]
+    fn parse_invalid_email_alias() {
+        assert!("".parse::<EmailAlias>().is_err());
+        assert!("me@".parse::<EmailAlias>().is_err());
+        assert!("me@localhost".parse::<EmailAlias>().is_err());
+        assert!("me@exa mple.com".parse::<EmailAlias>().is_err());
+    }
+}